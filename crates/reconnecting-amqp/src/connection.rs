@@ -1,6 +1,9 @@
 use amqprs::{
     BasicProperties,
-    channel::{BasicConsumeArguments, BasicPublishArguments, Channel, QueueDeclareArguments},
+    channel::{
+        BasicAckArguments, BasicConsumeArguments, BasicNackArguments, BasicPublishArguments,
+        BasicQosArguments, Channel, QueueDeclareArguments,
+    },
     connection::{Connection, OpenConnectionArguments},
 };
 use tokio::{
@@ -21,7 +24,7 @@ pub(crate) struct AmqpConnection {
     opts: ConnectionArguments,
     inner_opts: OpenConnectionArguments,
 
-    recv_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    recv_tx: Option<mpsc::Sender<Vec<u8>>>,
 
     send_tx: mpsc::UnboundedSender<Vec<u8>>,
     send_rx: mpsc::UnboundedReceiver<Vec<u8>>,
@@ -38,7 +41,7 @@ impl AmqpConnection {
         opts: ConnectionArguments,
         inner_opts: OpenConnectionArguments,
 
-        recv_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+        recv_tx: Option<mpsc::Sender<Vec<u8>>>,
 
         send_tx: mpsc::UnboundedSender<Vec<u8>>,
         send_rx: mpsc::UnboundedReceiver<Vec<u8>>,
@@ -103,7 +106,7 @@ struct AmqpSharedData {
     opts: ConnectionArguments,
     inner_opts: OpenConnectionArguments,
 
-    recv_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    recv_tx: Option<mpsc::Sender<Vec<u8>>>,
 
     send_tx: mpsc::UnboundedSender<Vec<u8>>,
     send_rx: mpsc::UnboundedReceiver<Vec<u8>>,
@@ -422,13 +425,31 @@ impl DeclaringConsumer {
     }
 
     async fn run(self, shared: &AmqpSharedData) -> State {
+        if let Some(prefetch_count) = shared.opts.prefetch_count {
+            tracing::debug!("setting prefetch count to {} ...", prefetch_count);
+            if let Err(err) = self
+                .chan
+                .basic_qos(BasicQosArguments::new(0, prefetch_count, false))
+                .await
+            {
+                let err = format!("error setting prefetch count: {err}");
+                tracing::error!(err);
+
+                if shared.start_tx.is_some() {
+                    return self.close_channel(CloseReason::StartError(Some(err.into())));
+                }
+
+                return self.close_channel(CloseReason::Other);
+            }
+        }
+
         tracing::debug!("declaring amqp consumer ...");
         if let Err(err) = self
             .chan
             .basic_consume(
                 AmqpConsumer::new(self.event_tx.clone()),
                 BasicConsumeArguments::new(&shared.opts.queue_name, "")
-                    .manual_ack(false)
+                    .manual_ack(shared.opts.prefetch_count.is_some())
                     .finish(),
             )
             .await
@@ -498,11 +519,35 @@ impl Connected {
                                 return self.close_channel(CloseReason::PublishNoRoute);
                             }
                         }
-                        Event::MessageReceived(_chan, _deliver, _basic_properties, data) => {
-                            if let Some(recv_tx) = &shared.recv_tx
-                                && let Err(err) = recv_tx.send(data) {
-                                    tracing::warn!("error sending received message to library user: {err}");
+                        Event::MessageReceived(_chan, deliver, _basic_properties, data) => {
+                            let mut delivered = true;
+                            if let Some(recv_tx) = &shared.recv_tx {
+                                // waits while the library user is busy, which leaves the
+                                // message unacknowledged and applies backpressure to the broker
+                                select! {
+                                    () = shared.shutdown.cancelled() => {
+                                        return self.close_channel(CloseReason::Shutdown);
+                                    }
+                                    result = recv_tx.send(data) => {
+                                        if let Err(err) = result {
+                                            tracing::warn!("error sending received message to library user, requeuing it: {err}");
+                                            delivered = false;
+                                        }
+                                    }
+                                }
+                            }
+
+                            if shared.opts.prefetch_count.is_some() {
+                                let result = if delivered {
+                                    self.chan.basic_ack(BasicAckArguments::new(deliver.delivery_tag(), false)).await
+                                } else {
+                                    // hand it back to the broker, so it isn't lost
+                                    self.chan.basic_nack(BasicNackArguments::new(deliver.delivery_tag(), false, true)).await
+                                };
+                                if let Err(err) = result {
+                                    tracing::error!("error acknowledging message: {err}");
                                 }
+                            }
                         }
                         _ => {}
                     }
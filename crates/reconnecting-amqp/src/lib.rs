@@ -25,6 +25,7 @@ type Error = Box<dyn std::error::Error + Send + Sync>;
 pub struct ConnectionArguments {
     reconnect_delay: Duration,
     queue_name: String,
+    prefetch_count: Option<u16>,
 }
 
 impl ConnectionArguments {
@@ -32,6 +33,7 @@ impl ConnectionArguments {
         Self {
             reconnect_delay: Duration::new(2, 0),
             queue_name: queue_name.into(),
+            prefetch_count: None,
         }
     }
 
@@ -39,6 +41,14 @@ impl ConnectionArguments {
         self.reconnect_delay = reconnect_delay;
         self
     }
+
+    /// only have this many unacknowledged messages delivered at a time, messages
+    /// are acknowledged once `recv_tx` accepts them, so a full `recv_tx` stops
+    /// the broker from sending more
+    pub fn prefetch_count(mut self, prefetch_count: u16) -> Self {
+        self.prefetch_count = Some(prefetch_count);
+        self
+    }
 }
 
 pub struct AmqpHandle {
@@ -53,7 +63,7 @@ impl AmqpHandle {
     pub fn new(
         inner_opts: OpenConnectionArguments,
         opts: ConnectionArguments,
-        recv_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+        recv_tx: Option<mpsc::Sender<Vec<u8>>>,
     ) -> Self {
         let (send_tx, send_rx) = mpsc::unbounded_channel();
         let (start_tx, start_rx) = oneshot::channel();
@@ -88,7 +98,7 @@ impl AmqpHandle {
     pub fn try_from_str(
         addr: &str,
         opts: ConnectionArguments,
-        recv_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+        recv_tx: Option<mpsc::Sender<Vec<u8>>>,
     ) -> Result<Self, Error> {
         Ok(Self::new(
             addr.try_into()
@@ -1,4 +1,7 @@
+use std::collections::HashSet;
+
 use twilight_cache_inmemory::ResourceType;
+use twilight_model::gateway::event::EventType;
 
 pub struct Config {
     pub resource_types: ResourceType,
@@ -26,6 +29,119 @@ impl Config {
     pub(crate) fn wants(&self, resource_type: ResourceType) -> bool {
         self.resource_types.contains(resource_type)
     }
+
+    /// the events that update the cached resources, other events can be
+    /// dropped before they reach the cache
+    pub fn event_types(&self) -> HashSet<EventType> {
+        // resources are also removed when their guild is
+        let guild_events = [EventType::GuildCreate, EventType::GuildDelete];
+
+        [
+            (
+                ResourceType::USER_CURRENT,
+                &[EventType::Ready, EventType::UserUpdate][..],
+            ),
+            (
+                ResourceType::GUILD,
+                &[EventType::Ready, EventType::GuildUpdate][..],
+            ),
+            (
+                ResourceType::CHANNEL,
+                &[
+                    EventType::ChannelCreate,
+                    EventType::ChannelDelete,
+                    EventType::ChannelPinsUpdate,
+                    EventType::ChannelUpdate,
+                    EventType::ThreadCreate,
+                    EventType::ThreadDelete,
+                    EventType::ThreadListSync,
+                    EventType::ThreadUpdate,
+                ][..],
+            ),
+            (ResourceType::EMOJI, &[EventType::GuildEmojisUpdate][..]),
+            (ResourceType::STICKER, &[EventType::GuildStickersUpdate][..]),
+            (
+                ResourceType::ROLE,
+                &[
+                    EventType::RoleCreate,
+                    EventType::RoleDelete,
+                    EventType::RoleUpdate,
+                ][..],
+            ),
+            (
+                ResourceType::MEMBER,
+                &[
+                    EventType::MemberAdd,
+                    EventType::MemberChunk,
+                    EventType::MemberRemove,
+                    EventType::MemberUpdate,
+                ][..],
+            ),
+            (
+                ResourceType::USER,
+                &[
+                    EventType::MemberAdd,
+                    EventType::MemberChunk,
+                    EventType::MemberRemove,
+                    EventType::MemberUpdate,
+                ][..],
+            ),
+            (ResourceType::PRESENCE, &[EventType::PresenceUpdate][..]),
+            (
+                ResourceType::MESSAGE,
+                &[
+                    EventType::MessageCreate,
+                    EventType::MessageDelete,
+                    EventType::MessageDeleteBulk,
+                    EventType::MessageUpdate,
+                    EventType::ReactionAdd,
+                    EventType::ReactionRemove,
+                    EventType::ReactionRemoveAll,
+                    EventType::ReactionRemoveEmoji,
+                ][..],
+            ),
+            (
+                ResourceType::VOICE_STATE,
+                &[EventType::VoiceStateUpdate][..],
+            ),
+            (
+                ResourceType::STAGE_INSTANCE,
+                &[
+                    EventType::StageInstanceCreate,
+                    EventType::StageInstanceDelete,
+                    EventType::StageInstanceUpdate,
+                ][..],
+            ),
+            (
+                ResourceType::INTEGRATION,
+                &[
+                    EventType::IntegrationCreate,
+                    EventType::IntegrationDelete,
+                    EventType::IntegrationUpdate,
+                ][..],
+            ),
+            (
+                ResourceType::GUILD_SCHEDULED_EVENT,
+                &[
+                    EventType::GuildScheduledEventCreate,
+                    EventType::GuildScheduledEventDelete,
+                    EventType::GuildScheduledEventUpdate,
+                    EventType::GuildScheduledEventUserAdd,
+                    EventType::GuildScheduledEventUserRemove,
+                ][..],
+            ),
+        ]
+        .into_iter()
+        .filter(|(resource_type, _)| self.wants(*resource_type))
+        .flat_map(|(resource_type, events)| {
+            let guild_events = match resource_type {
+                ResourceType::USER_CURRENT => &[][..],
+                _ => &guild_events[..],
+            };
+            events.iter().chain(guild_events).copied()
+        })
+        .collect()
+    }
 }
 
 impl Default for Config {
@@ -0,0 +1,47 @@
+use std::collections::{BTreeSet, HashSet};
+
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+
+use tulpje_framework::Error;
+
+/// hash of handler id to the json encoded names of the events it needs
+pub const SUBSCRIPTIONS_KEY: &str = "tulpje:event_subscriptions";
+/// notified after a handler changed its subscriptions, the payload is ignored
+pub const CHANGED_CHANNEL: &str = "tulpje:event_subscriptions:changed";
+
+/// replace the events `handler_id` needs, by their gateway names, e.g.
+/// `MESSAGE_CREATE`, and notify the gateways
+pub async fn publish(
+    redis: &RedisConnectionManager,
+    handler_id: u32,
+    events: &BTreeSet<String>,
+) -> Result<(), Error> {
+    let mut redis = redis.clone();
+
+    redis
+        .hset::<_, _, _, ()>(
+            SUBSCRIPTIONS_KEY,
+            handler_id,
+            serde_json::to_string(events)?,
+        )
+        .await?;
+    redis.publish::<_, _, ()>(CHANGED_CHANNEL, "").await?;
+
+    Ok(())
+}
+
+/// the events any handler needs, `None` when no handler published its
+/// subscriptions yet, in which case every event should be forwarded
+pub async fn load(redis: &RedisConnectionManager) -> Result<Option<HashSet<String>>, Error> {
+    let subscriptions: Vec<String> = redis.clone().hvals(SUBSCRIPTIONS_KEY).await?;
+    if subscriptions.is_empty() {
+        return Ok(None);
+    }
+
+    let mut events = HashSet::new();
+    for subscription in subscriptions {
+        events.extend(serde_json::from_str::<Vec<String>>(&subscription)?);
+    }
+
+    Ok(Some(events))
+}
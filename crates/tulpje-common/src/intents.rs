@@ -0,0 +1,32 @@
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+
+use tulpje_framework::Error;
+
+/// hash of handler id to the bits of the gateway intents its modules need
+pub const INTENTS_KEY: &str = "tulpje:intents";
+
+/// replace the intents `handler_id` needs, gateways only read them when they
+/// start, so changes take effect once they restart
+pub async fn publish(
+    redis: &RedisConnectionManager,
+    handler_id: u32,
+    intents: u64,
+) -> Result<(), Error> {
+    redis
+        .clone()
+        .hset::<_, _, _, ()>(INTENTS_KEY, handler_id, intents)
+        .await?;
+
+    Ok(())
+}
+
+/// the intents any handler needs, `None` when no handler published its
+/// intents yet
+pub async fn load(redis: &RedisConnectionManager) -> Result<Option<u64>, Error> {
+    let intents: Vec<u64> = redis.clone().hvals(INTENTS_KEY).await?;
+    if intents.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(intents.iter().fold(0, |all, intents| all | intents)))
+}
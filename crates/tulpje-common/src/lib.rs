@@ -2,9 +2,13 @@ use serde::{Deserialize, Serialize};
 
 use tulpje_framework::Metadata;
 
+pub mod event_subscriptions;
+pub mod intents;
 pub mod logging;
 pub mod metrics;
+pub mod runtime_config;
 pub mod shard_state;
+pub mod sharding;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DiscordEvent {
@@ -0,0 +1,263 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures_util::StreamExt as _;
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::watch, task::JoinHandle};
+
+use tulpje_framework::Error;
+
+/// json encoded [`RuntimeConfig`]
+pub const CONFIG_KEY: &str = "tulpje:config";
+/// notified after the config changed, the payload is ignored
+pub const CHANGED_CHANNEL: &str = "tulpje:config:changed";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// config is also reloaded this often, in case it was changed without
+/// publishing to [`CHANGED_CHANNEL`]
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// configuration that can be changed without restarting, stored in redis
+///
+/// missing fields are read from the `TULPJE_EXTRA_ERROR_MESSAGE` and
+/// `TULPJE_MESSAGE_CONTENT` env vars, or use their defaults, see
+/// [`RuntimeConfig::from_env`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// shown to users with internal errors, e.g. where to report them
+    pub error_message: String,
+    /// whether the gateway requests the message content intent, changing this
+    /// only takes effect once the gateway reconnects
+    pub message_content: bool,
+    /// feature flags, flags that aren't set are disabled
+    pub features: HashMap<String, bool>,
+    /// limits per module, e.g. `{"emoji": {"clone_max": 10}}`
+    pub limits: HashMap<String, HashMap<String, u64>>,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            error_message: String::new(),
+            message_content: true,
+            features: HashMap::new(),
+            limits: HashMap::new(),
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub fn from_env() -> Self {
+        Self {
+            error_message: std::env::var("TULPJE_EXTRA_ERROR_MESSAGE").unwrap_or_default(),
+            message_content: std::env::var("TULPJE_MESSAGE_CONTENT")
+                .unwrap_or_else(|_| "true".to_string())
+                == "true",
+            ..Default::default()
+        }
+    }
+
+    /// parse a possibly partial json config, fields it doesn't set are taken
+    /// from `base`
+    pub fn from_json(json: &str, base: &Self) -> Result<Self, Error> {
+        let serde_json::Value::Object(mut config) = serde_json::to_value(base)? else {
+            return Err("runtime config isn't a json object".into());
+        };
+        let serde_json::Value::Object(fields) = serde_json::from_str(json)? else {
+            return Err("runtime config should be a json object".into());
+        };
+        config.extend(fields);
+
+        Ok(serde_json::from_value(serde_json::Value::Object(config))?)
+    }
+
+    pub fn feature(&self, name: &str) -> bool {
+        self.features.get(name).copied().unwrap_or(false)
+    }
+
+    pub fn limit(&self, module: &str, name: &str) -> Option<u64> {
+        self.limits.get(module)?.get(name).copied()
+    }
+}
+
+/// the current [`RuntimeConfig`], kept up to date by [`listen`], cheap to clone
+#[derive(Debug, Clone)]
+pub struct RuntimeConfigHandle {
+    receiver: watch::Receiver<Arc<RuntimeConfig>>,
+    /// kept by fixed handles, so [`RuntimeConfigHandle::changed`] waits
+    /// instead of erroring right away
+    _sender: Option<Arc<watch::Sender<Arc<RuntimeConfig>>>>,
+}
+
+impl RuntimeConfigHandle {
+    /// a handle that never changes, for tools and tests
+    pub fn fixed(config: RuntimeConfig) -> Self {
+        let (sender, receiver) = watch::channel(Arc::new(config));
+        Self {
+            receiver,
+            _sender: Some(Arc::new(sender)),
+        }
+    }
+
+    pub fn get(&self) -> Arc<RuntimeConfig> {
+        Arc::clone(&self.receiver.borrow())
+    }
+
+    /// wait until the config changes, and return the new config
+    pub async fn changed(&mut self) -> Result<Arc<RuntimeConfig>, Error> {
+        self.receiver.changed().await?;
+        Ok(Arc::clone(&self.receiver.borrow_and_update()))
+    }
+}
+
+pub async fn load(redis: &RedisConnectionManager) -> Result<RuntimeConfig, Error> {
+    let config: Option<String> = redis.clone().get(CONFIG_KEY).await?;
+
+    Ok(match config {
+        Some(config) => RuntimeConfig::from_json(&config, &RuntimeConfig::from_env())?,
+        None => RuntimeConfig::from_env(),
+    })
+}
+
+/// replace the config and notify every process watching it
+pub async fn store(redis: &RedisConnectionManager, config: &RuntimeConfig) -> Result<(), Error> {
+    let mut redis = redis.clone();
+
+    redis
+        .set::<_, _, ()>(CONFIG_KEY, serde_json::to_string(config)?)
+        .await?;
+    redis.publish::<_, _, ()>(CHANGED_CHANNEL, "").await?;
+
+    Ok(())
+}
+
+/// load the config and keep it up to date in the background
+pub async fn listen(
+    client: redis::Client,
+    redis: RedisConnectionManager,
+) -> Result<(RuntimeConfigHandle, JoinHandle<()>), Error> {
+    let (sender, receiver) = watch::channel(Arc::new(load(&redis).await?));
+
+    let handle = tokio::spawn(async move {
+        loop {
+            if let Err(err) = subscribe(&client, &redis, &sender).await {
+                tracing::warn!("runtime config subscription failed: {}", err);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    Ok((
+        RuntimeConfigHandle {
+            receiver,
+            _sender: None,
+        },
+        handle,
+    ))
+}
+
+async fn subscribe(
+    client: &redis::Client,
+    redis: &RedisConnectionManager,
+    sender: &watch::Sender<Arc<RuntimeConfig>>,
+) -> Result<(), Error> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(CHANGED_CHANNEL).await?;
+
+    // catch up on changes made while we weren't subscribed
+    reload(redis, sender).await?;
+
+    let mut messages = pubsub.on_message();
+    let mut refresh = tokio::time::interval_at(
+        tokio::time::Instant::now() + REFRESH_INTERVAL,
+        REFRESH_INTERVAL,
+    );
+    loop {
+        tokio::select! {
+            message = messages.next() => {
+                if message.is_none() {
+                    return Err("runtime config subscription closed".into());
+                }
+            },
+            _ = refresh.tick() => {},
+        }
+
+        if let Err(err) = reload(redis, sender).await {
+            tracing::warn!("error reloading runtime config: {}", err);
+        }
+    }
+}
+
+async fn reload(
+    redis: &RedisConnectionManager,
+    sender: &watch::Sender<Arc<RuntimeConfig>>,
+) -> Result<(), Error> {
+    let config = load(redis).await?;
+
+    sender.send_if_modified(|current| {
+        if **current == config {
+            return false;
+        }
+
+        tracing::info!("runtime config changed");
+        *current = Arc::new(config);
+        true
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_config() {
+        let config = RuntimeConfig::from_json(
+            r#"{"features": {"new-stats": true}, "limits": {"emoji": {"clone_max": 10}}}"#,
+            &RuntimeConfig::default(),
+        )
+        .expect("couldn't parse config");
+
+        assert!(config.feature("new-stats"), "feature should be enabled");
+        assert!(
+            !config.feature("other"),
+            "unset features should be disabled"
+        );
+        assert_eq!(
+            config.limit("emoji", "clone_max"),
+            Some(10),
+            "limit should be set"
+        );
+        assert!(
+            config.message_content,
+            "missing fields should use their defaults"
+        );
+    }
+
+    #[test]
+    fn test_partial_config_base() {
+        let base = RuntimeConfig {
+            error_message: "report it in #support".into(),
+            message_content: false,
+            ..Default::default()
+        };
+        let config = RuntimeConfig::from_json(r#"{"features": {"new-stats": true}}"#, &base)
+            .expect("couldn't parse config");
+
+        assert!(config.feature("new-stats"), "feature should be enabled");
+        assert_eq!(
+            config.error_message, base.error_message,
+            "missing fields should be taken from the base"
+        );
+        assert!(
+            !config.message_content,
+            "missing fields should be taken from the base"
+        );
+
+        assert!(
+            RuntimeConfig::from_json("[]", &base).is_err(),
+            "config should be an object"
+        );
+    }
+}
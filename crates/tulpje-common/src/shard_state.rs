@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ShardState {
     pub shard_id: u32,
+    /// name of the gateway process running the shard, empty for states saved
+    /// by older versions
+    #[serde(default)]
+    pub process: String,
     pub guild_count: u64,
 
     pub up: bool,
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+use serde::{Deserialize, Serialize};
+
+use tulpje_framework::Error;
+
+/// json encoded [`Sharding`] the gateways currently run
+pub const SHARDING_KEY: &str = "tulpje:sharding";
+/// json encoded [`ShardingCommand`]s sent by the manager
+pub const COMMAND_CHANNEL: &str = "tulpje:sharding:commands";
+/// how long readiness and retirement of a generation are kept around,
+/// resharding that takes longer than this has failed anyway
+const READY_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// how shards are spread over gateway processes, every process runs the
+/// shards of one cluster, see `CLUSTER_ID` in the gateway config
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sharding {
+    /// increased on every reshard, so messages about an older reshard can be
+    /// told apart
+    pub generation: u64,
+    pub shard_count: u32,
+    pub cluster_size: u32,
+}
+
+/// resharding steps, sent by the manager to every gateway process
+///
+/// 1. [`ShardingCommand::Prepare`], gateways connect the new shards next to
+///    the old ones, without forwarding their events, and mark them ready
+/// 2. [`ShardingCommand::Commit`] once every new shard is ready, gateways
+///    forward events from the new shards and disconnect the old ones
+/// 3. or [`ShardingCommand::Abort`], gateways disconnect the new shards
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ShardingCommand {
+    Prepare(Sharding),
+    Commit { generation: u64 },
+    Abort { generation: u64 },
+}
+
+fn ready_key(generation: u64) -> String {
+    format!("tulpje:sharding:{}:ready", generation)
+}
+
+fn retired_key(generation: u64) -> String {
+    format!("tulpje:sharding:{}:retired", generation)
+}
+
+pub async fn load(redis: &RedisConnectionManager) -> Result<Option<Sharding>, Error> {
+    let sharding: Option<String> = redis.clone().get(SHARDING_KEY).await?;
+
+    Ok(match sharding {
+        Some(sharding) => Some(serde_json::from_str(&sharding)?),
+        None => None,
+    })
+}
+
+pub async fn store(redis: &RedisConnectionManager, sharding: &Sharding) -> Result<(), Error> {
+    redis
+        .clone()
+        .set::<_, _, ()>(SHARDING_KEY, serde_json::to_string(sharding)?)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn send(redis: &RedisConnectionManager, command: &ShardingCommand) -> Result<(), Error> {
+    redis
+        .clone()
+        .publish::<_, _, ()>(COMMAND_CHANNEL, serde_json::to_string(command)?)
+        .await?;
+
+    Ok(())
+}
+
+/// mark a shard of `generation` as ready, called by the gateway running it
+pub async fn mark_ready(
+    redis: &RedisConnectionManager,
+    generation: u64,
+    shard_id: u32,
+) -> Result<(), Error> {
+    let mut redis = redis.clone();
+    let key = ready_key(generation);
+
+    redis.sadd::<_, _, ()>(&key, shard_id).await?;
+    redis
+        .expire::<_, ()>(&key, i64::try_from(READY_TTL.as_secs())?)
+        .await?;
+
+    Ok(())
+}
+
+/// how many shards of `generation` are ready
+pub async fn ready_count(redis: &RedisConnectionManager, generation: u64) -> Result<u32, Error> {
+    Ok(redis.clone().scard(ready_key(generation)).await?)
+}
+
+/// mark that `cluster_id` switched to `generation` and retired its older
+/// shards, called by the gateway running the cluster
+pub async fn mark_retired(
+    redis: &RedisConnectionManager,
+    generation: u64,
+    cluster_id: u32,
+) -> Result<(), Error> {
+    let mut redis = redis.clone();
+    let key = retired_key(generation);
+
+    redis.sadd::<_, _, ()>(&key, cluster_id).await?;
+    redis
+        .expire::<_, ()>(&key, i64::try_from(READY_TTL.as_secs())?)
+        .await?;
+
+    Ok(())
+}
+
+/// how many clusters switched to `generation`
+pub async fn retired_count(redis: &RedisConnectionManager, generation: u64) -> Result<u32, Error> {
+    Ok(redis.clone().scard(retired_key(generation)).await?)
+}
+
+/// forget which shards of `generation` were ready and which clusters retired
+/// their older shards
+pub async fn forget(redis: &RedisConnectionManager, generation: u64) -> Result<(), Error> {
+    redis
+        .clone()
+        .del::<_, ()>(vec![ready_key(generation), retired_key(generation)])
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_format() {
+        let command = ShardingCommand::Prepare(Sharding {
+            generation: 2,
+            shard_count: 16,
+            cluster_size: 4,
+        });
+
+        let json = serde_json::to_string(&command).expect("couldn't serialize command");
+        assert_eq!(
+            json, r#"{"command":"prepare","generation":2,"shard_count":16,"cluster_size":4}"#,
+            "commands should be tagged with their name"
+        );
+        assert_eq!(
+            serde_json::from_str::<ShardingCommand>(&json).ok(),
+            Some(command),
+            "commands should round trip"
+        );
+    }
+}
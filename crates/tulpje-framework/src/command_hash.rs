@@ -0,0 +1,126 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use twilight_model::application::command::Command;
+
+/// hash of every command's definition, keyed by command type and name
+pub type CommandHashes = BTreeMap<String, String>;
+
+/// hash command definitions so changes can be detected without asking discord,
+/// commands are serialized through `serde_json::Value` which sorts object keys,
+/// so localization `HashMap`s don't affect the result
+pub fn hash_commands(commands: &[Command]) -> Result<CommandHashes, serde_json::Error> {
+    commands
+        .iter()
+        .map(|command| {
+            let canonical = serde_json::to_value(command)?.to_string();
+            Ok((
+                format!("{:?}:{}", command.kind, command.name),
+                format!("{:016x}", fnv1a(canonical.as_bytes())),
+            ))
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a, unlike `DefaultHasher` it's stable between rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CommandDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl CommandDiff {
+    pub fn new(old: &CommandHashes, new: &CommandHashes) -> Self {
+        let mut diff = Self::default();
+
+        for (name, hash) in new {
+            match old.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(old_hash) if old_hash != hash => diff.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|name| !new.contains_key(*name))
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Display for CommandDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "added [{}], removed [{}], changed [{}]",
+            self.added.join(", "),
+            self.removed.join(", "),
+            self.changed.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::application::command::CommandType;
+
+    use super::*;
+    use crate::module::command_builder::CommandBuilder;
+
+    fn command(name: &str, description: &str) -> Command {
+        CommandBuilder::<()>::new(name, description, CommandType::ChatInput).build()
+    }
+
+    #[test]
+    fn test_hash_stable() {
+        let commands = [command("stats", "show stats")];
+
+        assert_eq!(
+            hash_commands(&commands).expect("couldn't hash commands"),
+            hash_commands(&commands).expect("couldn't hash commands"),
+            "hashing the same commands should give the same result"
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = hash_commands(&[command("stats", "show stats"), command("ping", "pong")])
+            .expect("couldn't hash commands");
+        let new = hash_commands(&[
+            command("stats", "show statistics"),
+            command("emoji", "emoji"),
+        ])
+        .expect("couldn't hash commands");
+
+        let diff = CommandDiff::new(&old, &new);
+        assert_eq!(
+            diff,
+            CommandDiff {
+                added: vec!["ChatInput:emoji".into()],
+                removed: vec!["ChatInput:ping".into()],
+                changed: vec!["ChatInput:stats".into()],
+            },
+            "diff should contain added, removed and changed commands"
+        );
+
+        assert!(
+            CommandDiff::new(&new, &new).is_empty(),
+            "identical commands shouldn't produce a diff"
+        );
+    }
+}
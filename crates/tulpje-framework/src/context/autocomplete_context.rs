@@ -1 +1,130 @@
+use std::{collections::HashMap, sync::Arc};
 
+use twilight_http::{Client, client::InteractionClient, response::marker::EmptyBody};
+use twilight_model::{
+    application::{
+        command::{CommandOptionChoice, CommandOptionChoiceValue, CommandOptionType},
+        interaction::application_command::{CommandData, CommandDataOption, CommandOptionValue},
+    },
+    gateway::payload::incoming::InteractionCreate,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{Id, marker::ApplicationMarker},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use super::Context;
+use crate::{
+    Metadata,
+    i18n::{Args, Catalogue, interaction_locales},
+};
+
+/// discord doesn't accept more than 25 autocomplete choices
+const MAX_CHOICES: usize = 25;
+
+/// the option the user is currently typing in
+#[derive(Clone, Debug)]
+pub struct FocusedOption {
+    pub name: String,
+    pub value: String,
+    pub kind: CommandOptionType,
+}
+
+#[derive(Clone, Debug)]
+pub struct AutocompleteContext<T: Clone + Send + Sync> {
+    pub meta: Metadata,
+    pub application_id: Id<ApplicationMarker>,
+    pub services: Arc<T>,
+    pub client: Arc<Client>,
+
+    pub event: InteractionCreate,
+    pub command: CommandData,
+
+    pub name: String,
+    pub focused: FocusedOption,
+    pub options: HashMap<String, CommandOptionValue>,
+
+    /// messages of the handler's module, see [`crate::ModuleBuilder::locale`]
+    pub catalogue: Arc<Catalogue>,
+}
+
+impl<T: Clone + Send + Sync> AutocompleteContext<T> {
+    pub fn from_context(
+        meta: Metadata,
+        ctx: Context<T>,
+
+        event: InteractionCreate,
+        command: CommandData,
+
+        name: String,
+        focused: FocusedOption,
+        options: &[CommandDataOption],
+    ) -> Self {
+        Self {
+            meta,
+            application_id: ctx.application_id,
+            client: ctx.client,
+            services: ctx.services,
+
+            command,
+            event,
+
+            name,
+            focused,
+            options: options
+                .iter()
+                .cloned()
+                .map(|opt| (opt.name, opt.value))
+                .collect(),
+
+            catalogue: Arc::default(),
+        }
+    }
+
+    /// `key` from the module's messages, in the user's or the guild's locale
+    pub fn t(&self, key: &str, args: &Args<'_>) -> String {
+        self.catalogue
+            .format(&interaction_locales(&self.event), key, args)
+    }
+
+    pub fn interaction(&self) -> InteractionClient<'_> {
+        self.client.interaction(self.application_id)
+    }
+
+    /// respond with the provided choices, anything past the first 25 is dropped
+    pub async fn respond(
+        &self,
+        choices: impl IntoIterator<Item = CommandOptionChoice>,
+    ) -> Result<twilight_http::Response<EmptyBody>, twilight_http::Error> {
+        let response = InteractionResponseDataBuilder::new()
+            .choices(choices.into_iter().take(MAX_CHOICES))
+            .build();
+
+        self.interaction()
+            .create_response(
+                self.event.id,
+                &self.event.token,
+                &InteractionResponse {
+                    kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+                    data: Some(response),
+                },
+            )
+            .await
+    }
+
+    /// respond with `(name, value)` pairs for string options
+    pub async fn respond_strings<K: Into<String>, V: Into<String>>(
+        &self,
+        choices: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<twilight_http::Response<EmptyBody>, twilight_http::Error> {
+        self.respond(
+            choices
+                .into_iter()
+                .map(|(name, value)| CommandOptionChoice {
+                    name: name.into(),
+                    name_localizations: None,
+                    value: CommandOptionChoiceValue::String(value.into()),
+                }),
+        )
+        .await
+    }
+}
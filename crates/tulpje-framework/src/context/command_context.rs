@@ -1,20 +1,31 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
 
 use twilight_http::{Client, client::InteractionClient, response::marker::EmptyBody};
 use twilight_model::{
-    application::interaction::application_command::{
-        CommandData, CommandDataOption, CommandOptionValue,
+    application::interaction::{
+        InteractionChannel, InteractionDataResolved, InteractionMember,
+        application_command::{CommandData, CommandDataOption, CommandOptionValue},
     },
-    channel::{Message, message::MessageFlags},
+    channel::{Attachment, Message, message::MessageFlags},
     gateway::payload::incoming::InteractionCreate,
-    guild::Guild,
+    guild::{Guild, Role},
     http::interaction::{InteractionResponse, InteractionResponseType},
-    id::{Id, marker::ApplicationMarker},
+    id::{
+        Id,
+        marker::{ApplicationMarker, RoleMarker, UserMarker},
+    },
+    user::User,
 };
 use twilight_util::builder::InteractionResponseDataBuilder;
 
 use super::Context;
-use crate::{Error, Metadata};
+use crate::{
+    Error, Metadata,
+    i18n::{Args, Catalogue, interaction_locales},
+};
 
 #[derive(Clone, Debug)]
 pub struct CommandContext<T: Clone + Send + Sync> {
@@ -28,6 +39,23 @@ pub struct CommandContext<T: Clone + Send + Sync> {
 
     pub name: String,
     pub options: HashMap<String, CommandOptionValue>,
+
+    /// messages of the handler's module, see [`crate::ModuleBuilder::locale`]
+    pub catalogue: Arc<Catalogue>,
+
+    /// how the interaction was answered through [`Self::response`], shared
+    /// between clones so error reporting knows what's still possible
+    acknowledgement: Arc<Mutex<Acknowledgement>>,
+}
+
+/// how a command interaction has been answered so far
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Acknowledgement {
+    #[default]
+    None,
+    /// a loading message was sent, which can be edited into the actual response
+    Deferred,
+    Responded,
 }
 
 impl<T: Clone + Send + Sync> CommandContext<T> {
@@ -56,9 +84,28 @@ impl<T: Clone + Send + Sync> CommandContext<T> {
                 .cloned()
                 .map(|opt| (opt.name, opt.value))
                 .collect(),
+
+            catalogue: Arc::default(),
+
+            acknowledgement: Arc::default(),
         }
     }
 
+    /// how the interaction has been answered so far, only tracks responses
+    /// sent through [`Self::response`] and the helpers using it
+    pub fn acknowledgement(&self) -> Acknowledgement {
+        *self
+            .acknowledgement
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// `key` from the module's messages, in the user's or the guild's locale
+    pub fn t(&self, key: &str, args: &Args<'_>) -> String {
+        self.catalogue
+            .format(&interaction_locales(&self.event), key, args)
+    }
+
     pub fn interaction(&self) -> InteractionClient<'_> {
         self.client.interaction(self.application_id)
     }
@@ -79,9 +126,20 @@ impl<T: Clone + Send + Sync> CommandContext<T> {
         &self,
         response: InteractionResponse,
     ) -> Result<twilight_http::Response<EmptyBody>, twilight_http::Error> {
-        self.interaction()
+        let result = self
+            .interaction()
             .create_response(self.event.id, &self.event.token, &response)
-            .await
+            .await?;
+
+        *self
+            .acknowledgement
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = match response.kind {
+            InteractionResponseType::DeferredChannelMessageWithSource => Acknowledgement::Deferred,
+            _ => Acknowledgement::Responded,
+        };
+
+        Ok(result)
     }
 
     pub async fn update(
@@ -146,4 +204,496 @@ impl<T: Clone + Send + Sync> CommandContext<T> {
         self.get_arg_string_optional(name)?
             .ok_or_else(|| format!("couldn't find command argument {}", name).into())
     }
+
+    pub fn get_arg_integer_optional(&self, name: &str) -> Result<Option<i64>, Error> {
+        self.get_arg_optional(name, "an integer", |value| match value {
+            CommandOptionValue::Integer(value) => Some(*value),
+            _ => None,
+        })
+    }
+
+    pub fn get_arg_integer(&self, name: &str) -> Result<i64, Error> {
+        required(name, self.get_arg_integer_optional(name)?)
+    }
+
+    pub fn get_arg_number_optional(&self, name: &str) -> Result<Option<f64>, Error> {
+        self.get_arg_optional(name, "a number", |value| match value {
+            CommandOptionValue::Number(value) => Some(*value),
+            _ => None,
+        })
+    }
+
+    pub fn get_arg_number(&self, name: &str) -> Result<f64, Error> {
+        required(name, self.get_arg_number_optional(name)?)
+    }
+
+    pub fn get_arg_bool_optional(&self, name: &str) -> Result<Option<bool>, Error> {
+        self.get_arg_optional(name, "a boolean", |value| match value {
+            CommandOptionValue::Boolean(value) => Some(*value),
+            _ => None,
+        })
+    }
+
+    pub fn get_arg_bool(&self, name: &str) -> Result<bool, Error> {
+        required(name, self.get_arg_bool_optional(name)?)
+    }
+
+    pub fn get_arg_user_optional(&self, name: &str) -> Result<Option<User>, Error> {
+        let Some(user_id) = self.get_arg_optional(name, "a user", |value| match value {
+            CommandOptionValue::User(user_id) => Some(*user_id),
+            _ => None,
+        })?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            self.resolved()?
+                .users
+                .get(&user_id)
+                .cloned()
+                .ok_or_else(|| format!("user {} for option '{}' not resolved", user_id, name))?,
+        ))
+    }
+
+    pub fn get_arg_user(&self, name: &str) -> Result<User, Error> {
+        required(name, self.get_arg_user_optional(name)?)
+    }
+
+    /// get the guild member data for a user option, discord only sends this
+    /// for users that are a member of the guild the command was used in
+    pub fn get_arg_member_optional(&self, name: &str) -> Result<Option<InteractionMember>, Error> {
+        let Some(user_id) = self.get_arg_optional(name, "a user", |value| match value {
+            CommandOptionValue::User(user_id) => Some(*user_id),
+            _ => None,
+        })?
+        else {
+            return Ok(None);
+        };
+
+        Ok(self.resolved()?.members.get(&user_id).cloned())
+    }
+
+    pub fn get_arg_member(&self, name: &str) -> Result<InteractionMember, Error> {
+        required(name, self.get_arg_member_optional(name)?)
+    }
+
+    pub fn get_arg_channel_optional(
+        &self,
+        name: &str,
+    ) -> Result<Option<InteractionChannel>, Error> {
+        let Some(channel_id) = self.get_arg_optional(name, "a channel", |value| match value {
+            CommandOptionValue::Channel(channel_id) => Some(*channel_id),
+            _ => None,
+        })?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            self.resolved()?
+                .channels
+                .get(&channel_id)
+                .cloned()
+                .ok_or_else(|| {
+                    format!("channel {} for option '{}' not resolved", channel_id, name)
+                })?,
+        ))
+    }
+
+    pub fn get_arg_channel(&self, name: &str) -> Result<InteractionChannel, Error> {
+        required(name, self.get_arg_channel_optional(name)?)
+    }
+
+    pub fn get_arg_role_optional(&self, name: &str) -> Result<Option<Role>, Error> {
+        let Some(role_id) = self.get_arg_optional(name, "a role", |value| match value {
+            CommandOptionValue::Role(role_id) => Some(*role_id),
+            _ => None,
+        })?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            self.resolved()?
+                .roles
+                .get(&role_id)
+                .cloned()
+                .ok_or_else(|| format!("role {} for option '{}' not resolved", role_id, name))?,
+        ))
+    }
+
+    pub fn get_arg_role(&self, name: &str) -> Result<Role, Error> {
+        required(name, self.get_arg_role_optional(name)?)
+    }
+
+    pub fn get_arg_mentionable_optional(&self, name: &str) -> Result<Option<Mentionable>, Error> {
+        let Some(id) = self.get_arg_optional(name, "a mentionable", |value| match value {
+            CommandOptionValue::Mentionable(id) => Some(*id),
+            _ => None,
+        })?
+        else {
+            return Ok(None);
+        };
+
+        // mentionables can be either a user or a role, discord tells us which
+        // by putting it in the relevant resolved map
+        let resolved = self.resolved()?;
+        if let Some(user) = resolved.users.get(&id.cast::<UserMarker>()) {
+            return Ok(Some(Mentionable::User(Box::new(user.clone()))));
+        }
+        if let Some(role) = resolved.roles.get(&id.cast::<RoleMarker>()) {
+            return Ok(Some(Mentionable::Role(Box::new(role.clone()))));
+        }
+
+        Err(format!("mentionable {} for option '{}' not resolved", id, name).into())
+    }
+
+    pub fn get_arg_mentionable(&self, name: &str) -> Result<Mentionable, Error> {
+        required(name, self.get_arg_mentionable_optional(name)?)
+    }
+
+    pub fn get_arg_attachment_optional(&self, name: &str) -> Result<Option<Attachment>, Error> {
+        let Some(attachment_id) =
+            self.get_arg_optional(name, "an attachment", |value| match value {
+                CommandOptionValue::Attachment(attachment_id) => Some(*attachment_id),
+                _ => None,
+            })?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            self.resolved()?
+                .attachments
+                .get(&attachment_id)
+                .cloned()
+                .ok_or_else(|| {
+                    format!(
+                        "attachment {} for option '{}' not resolved",
+                        attachment_id, name
+                    )
+                })?,
+        ))
+    }
+
+    pub fn get_arg_attachment(&self, name: &str) -> Result<Attachment, Error> {
+        required(name, self.get_arg_attachment_optional(name)?)
+    }
+
+    fn get_arg_optional<V>(
+        &self,
+        name: &str,
+        kind: &str,
+        extract: impl FnOnce(&CommandOptionValue) -> Option<V>,
+    ) -> Result<Option<V>, Error> {
+        let Some(value) = self.options.get(name) else {
+            return Ok(None);
+        };
+
+        Ok(Some(extract(value).ok_or_else(|| {
+            format!("option '{}' not {} option", name, kind)
+        })?))
+    }
+
+    fn resolved(&self) -> Result<&InteractionDataResolved, Error> {
+        self.command
+            .resolved
+            .as_ref()
+            .ok_or_else(|| "command has no resolved data".into())
+    }
+}
+
+/// the value of a mentionable option, which can either be a user or a role
+#[derive(Clone, Debug)]
+pub enum Mentionable {
+    User(Box<User>),
+    Role(Box<Role>),
+}
+
+fn required<V>(name: &str, value: Option<V>) -> Result<V, Error> {
+    value.ok_or_else(|| format!("couldn't find command argument {}", name).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const USER_ID: u64 = 10;
+    const ROLE_ID: u64 = 20;
+    const CHANNEL_ID: u64 = 30;
+    const ATTACHMENT_ID: u64 = 40;
+
+    fn resolved() -> serde_json::Value {
+        json!({
+            "users": {
+                USER_ID.to_string(): {
+                    "id": USER_ID.to_string(),
+                    "username": "user",
+                    "discriminator": "0",
+                    "avatar": null,
+                },
+            },
+            "members": {
+                USER_ID.to_string(): {
+                    "roles": [ROLE_ID.to_string()],
+                    "joined_at": "2024-01-01T00:00:00.000000+00:00",
+                    "nick": "member",
+                    "pending": false,
+                    "permissions": "0",
+                    "flags": 0,
+                },
+            },
+            "roles": {
+                ROLE_ID.to_string(): {
+                    "id": ROLE_ID.to_string(),
+                    "name": "role",
+                    "color": 0,
+                    "colors": {"primary_color": 0},
+                    "hoist": false,
+                    "managed": false,
+                    "mentionable": true,
+                    "permissions": "0",
+                    "position": 1,
+                    "flags": 0,
+                },
+            },
+            "channels": {
+                CHANNEL_ID.to_string(): {
+                    "id": CHANNEL_ID.to_string(),
+                    "name": "general",
+                    "type": 0,
+                    "permissions": "0",
+                },
+            },
+            "attachments": {
+                ATTACHMENT_ID.to_string(): {
+                    "id": ATTACHMENT_ID.to_string(),
+                    "filename": "emoji.png",
+                    "content_type": "image/png",
+                    "size": 100,
+                    "url": "https://cdn.discordapp.com/emoji.png",
+                    "proxy_url": "https://media.discordapp.net/emoji.png",
+                },
+            },
+        })
+    }
+
+    /// a context for `/test` with `options`, `resolved` is left out when null
+    fn context(options: serde_json::Value, resolved: serde_json::Value) -> CommandContext<()> {
+        let mut data = json!({
+            "id": "6",
+            "name": "test",
+            "type": 1,
+            "options": options,
+        });
+        if !resolved.is_null() {
+            data["resolved"] = resolved;
+        }
+
+        let event = InteractionCreate(
+            serde_json::from_value(json!({
+                "id": "5",
+                "application_id": "1",
+                "type": 2,
+                "token": "token",
+                "guild_id": "2",
+                "authorizing_integration_owners": {},
+                "entitlements": [],
+                "data": data.clone(),
+            }))
+            .expect("couldn't parse interaction"),
+        );
+        let command: CommandData = serde_json::from_value(data).expect("couldn't parse command");
+        let options = command.options.clone();
+
+        CommandContext::from_context(
+            Metadata {
+                uuid: uuid::Uuid::now_v7(),
+                shard: 0,
+            },
+            Context {
+                application_id: Id::new(1),
+                services: Arc::new(()),
+                client: Arc::new(Client::new(String::new())),
+                error_message: None,
+            },
+            event,
+            command,
+            "test".into(),
+            &options,
+        )
+    }
+
+    #[test]
+    fn test_get_arg_primitives() {
+        let ctx = context(
+            json!([
+                {"name": "string", "type": 3, "value": "text"},
+                {"name": "integer", "type": 4, "value": 5},
+                {"name": "number", "type": 10, "value": 1.5},
+                {"name": "bool", "type": 5, "value": true},
+            ]),
+            serde_json::Value::Null,
+        );
+
+        assert_eq!(
+            ctx.get_arg_string("string").expect("string should be set"),
+            "text",
+            "string option should be returned"
+        );
+        assert_eq!(
+            ctx.get_arg_integer("integer")
+                .expect("integer should be set"),
+            5,
+            "integer option should be returned"
+        );
+        assert!(
+            (ctx.get_arg_number("number").expect("number should be set") - 1.5).abs()
+                < f64::EPSILON,
+            "number option should be returned"
+        );
+        assert!(
+            ctx.get_arg_bool("bool").expect("bool should be set"),
+            "bool option should be returned"
+        );
+
+        assert!(
+            ctx.get_arg_integer_optional("missing")
+                .expect("missing options aren't an error")
+                .is_none(),
+            "missing optional options should be none"
+        );
+        assert!(
+            ctx.get_arg_integer("missing").is_err(),
+            "missing required options should be an error"
+        );
+    }
+
+    #[test]
+    fn test_get_arg_resolved() {
+        let ctx = context(
+            json!([
+                {"name": "user", "type": 6, "value": USER_ID.to_string()},
+                {"name": "role", "type": 8, "value": ROLE_ID.to_string()},
+                {"name": "channel", "type": 7, "value": CHANNEL_ID.to_string()},
+                {"name": "attachment", "type": 11, "value": ATTACHMENT_ID.to_string()},
+                {"name": "mentioned_user", "type": 9, "value": USER_ID.to_string()},
+                {"name": "mentioned_role", "type": 9, "value": ROLE_ID.to_string()},
+            ]),
+            resolved(),
+        );
+
+        assert_eq!(
+            ctx.get_arg_user("user").expect("user should resolve").id,
+            Id::new(USER_ID),
+            "user should be resolved"
+        );
+        assert_eq!(
+            ctx.get_arg_member("user")
+                .expect("member should resolve")
+                .nick
+                .as_deref(),
+            Some("member"),
+            "member should be resolved"
+        );
+        assert_eq!(
+            ctx.get_arg_role("role").expect("role should resolve").id,
+            Id::new(ROLE_ID),
+            "role should be resolved"
+        );
+        assert_eq!(
+            ctx.get_arg_channel("channel")
+                .expect("channel should resolve")
+                .id,
+            Id::new(CHANNEL_ID),
+            "channel should be resolved"
+        );
+        assert_eq!(
+            ctx.get_arg_attachment("attachment")
+                .expect("attachment should resolve")
+                .filename,
+            "emoji.png",
+            "attachment should be resolved"
+        );
+        assert!(
+            matches!(
+                ctx.get_arg_mentionable("mentioned_user"),
+                Ok(Mentionable::User(user)) if user.id == Id::new(USER_ID)
+            ),
+            "mentionable should resolve to the user"
+        );
+        assert!(
+            matches!(
+                ctx.get_arg_mentionable("mentioned_role"),
+                Ok(Mentionable::Role(role)) if role.id == Id::new(ROLE_ID)
+            ),
+            "mentionable should resolve to the role"
+        );
+    }
+
+    #[test]
+    fn test_get_arg_wrong_type() {
+        let ctx = context(
+            json!([
+                {"name": "string", "type": 3, "value": "text"},
+                {"name": "user", "type": 6, "value": USER_ID.to_string()},
+            ]),
+            resolved(),
+        );
+
+        assert!(
+            ctx.get_arg_integer("string").is_err(),
+            "string option shouldn't be returned as integer"
+        );
+        assert!(
+            ctx.get_arg_string_optional("user").is_err(),
+            "user option shouldn't be returned as string, even when optional"
+        );
+        assert!(
+            ctx.get_arg_role("user").is_err(),
+            "user option shouldn't be returned as role"
+        );
+        assert!(
+            ctx.get_arg_user("string").is_err(),
+            "string option shouldn't be returned as user"
+        );
+    }
+
+    #[test]
+    fn test_get_arg_unresolved() {
+        let options = json!([
+            {"name": "user", "type": 6, "value": USER_ID.to_string()},
+            {"name": "role", "type": 8, "value": ROLE_ID.to_string()},
+            {"name": "mentionable", "type": 9, "value": "99"},
+        ]);
+
+        let ctx = context(options.clone(), serde_json::Value::Null);
+        assert!(
+            ctx.get_arg_user("user").is_err(),
+            "user without resolved data should be an error"
+        );
+        assert!(
+            ctx.get_arg_role("role").is_err(),
+            "role without resolved data should be an error"
+        );
+
+        let ctx = context(options, json!({}));
+        assert!(
+            ctx.get_arg_user("user").is_err(),
+            "user missing from resolved data should be an error"
+        );
+        assert!(
+            ctx.get_arg_member_optional("user")
+                .expect("members are optional")
+                .is_none(),
+            "member missing from resolved data should be none"
+        );
+        assert!(
+            ctx.get_arg_mentionable("mentionable").is_err(),
+            "mentionable missing from resolved data should be an error"
+        );
+    }
 }
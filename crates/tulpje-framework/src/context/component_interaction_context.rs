@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use twilight_http::{Client, client::InteractionClient, response::marker::EmptyBody};
 use twilight_model::{
@@ -10,7 +10,10 @@ use twilight_model::{
 };
 
 use super::Context;
-use crate::{Error, Metadata};
+use crate::{
+    Error, Metadata,
+    i18n::{Args, Catalogue, interaction_locales},
+};
 
 #[derive(Clone, Debug)]
 pub struct ComponentInteractionContext<T: Clone + Send + Sync> {
@@ -21,6 +24,12 @@ pub struct ComponentInteractionContext<T: Clone + Send + Sync> {
 
     pub event: InteractionCreate,
     pub interaction: MessageComponentInteractionData,
+
+    /// parameters captured from the `custom_id` by the handler's pattern
+    pub params: HashMap<String, String>,
+
+    /// messages of the handler's module, see [`crate::ModuleBuilder::locale`]
+    pub catalogue: Arc<Catalogue>,
 }
 
 impl<T: Clone + Send + Sync> ComponentInteractionContext<T> {
@@ -38,9 +47,33 @@ impl<T: Clone + Send + Sync> ComponentInteractionContext<T> {
             meta,
             interaction,
             event,
+
+            params: HashMap::new(),
+
+            catalogue: Arc::default(),
         }
     }
 
+    /// `key` from the module's messages, in the user's or the guild's locale
+    pub fn t(&self, key: &str, args: &Args<'_>) -> String {
+        self.catalogue
+            .format(&interaction_locales(&self.event), key, args)
+    }
+
+    pub fn param(&self, name: &str) -> Result<&str, Error> {
+        self.params
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| format!("custom_id parameter '{}' not found", name).into())
+    }
+
+    pub fn parse_param<V: FromStr>(&self, name: &str) -> Result<V, Error>
+    where
+        V::Err: std::error::Error + Send + Sync + 'static,
+    {
+        Ok(self.param(name)?.parse()?)
+    }
+
     pub fn interaction(&self) -> InteractionClient<'_> {
         self.client.interaction(self.application_id)
     }
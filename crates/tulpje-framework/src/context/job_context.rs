@@ -0,0 +1,27 @@
+use std::sync::Arc;
+use twilight_http::Client;
+use twilight_model::id::{Id, marker::ApplicationMarker};
+
+use super::Context;
+use crate::job::Job;
+
+#[derive(Debug)]
+pub struct JobContext<T: Clone + Send + Sync> {
+    pub application_id: Id<ApplicationMarker>,
+    pub services: Arc<T>,
+    pub client: Arc<Client>,
+
+    pub job: Job,
+}
+
+impl<T: Clone + Send + Sync> JobContext<T> {
+    pub fn from_context(ctx: Context<T>, job: Job) -> Self {
+        Self {
+            application_id: ctx.application_id,
+            services: ctx.services,
+            client: ctx.client,
+
+            job,
+        }
+    }
+}
@@ -7,26 +7,41 @@ pub mod autocomplete_context;
 pub mod command_context;
 pub mod component_interaction_context;
 pub mod event_context;
+pub mod job_context;
 pub mod modal_context;
 pub mod task_context;
 
-pub use command_context::CommandContext;
+pub use autocomplete_context::AutocompleteContext;
+pub use command_context::{Acknowledgement, CommandContext};
 pub use component_interaction_context::ComponentInteractionContext;
 pub use event_context::EventContext;
+pub use job_context::JobContext;
 pub use modal_context::ModalContext;
 pub use task_context::TaskContext;
 
+/// returns the extra message shown with internal errors, see
+/// [`crate::framework::FrameworkBuilder::error_message`]
+pub type ErrorMessageFunc<T> = fn(&T) -> String;
+
 #[derive(Debug)]
 pub struct Context<T: Clone + Send + Sync> {
     pub application_id: Id<ApplicationMarker>,
     pub services: Arc<T>,
     pub client: Arc<Client>,
+    pub error_message: Option<ErrorMessageFunc<T>>,
 }
 
 impl<T: Clone + Send + Sync> Context<T> {
     pub fn interaction(&self) -> InteractionClient<'_> {
         self.client.interaction(self.application_id)
     }
+
+    /// extra message shown with internal errors, empty if there's none
+    pub fn error_message(&self) -> String {
+        self.error_message
+            .map(|func| func(&self.services))
+            .unwrap_or_default()
+    }
 }
 
 impl<T: Clone + Send + Sync> Clone for Context<T> {
@@ -35,11 +50,13 @@ impl<T: Clone + Send + Sync> Clone for Context<T> {
             application_id: self.application_id,
             services: Arc::clone(&self.services),
             client: Arc::clone(&self.client),
+            error_message: self.error_message,
         }
     }
 }
 
 pub enum InteractionContext<T: Clone + Send + Sync> {
+    Autocomplete(AutocompleteContext<T>),
     Command(CommandContext<T>),
     ComponentInteraction(ComponentInteractionContext<T>),
     Modal(ModalContext<T>),
@@ -1,14 +1,19 @@
 use std::sync::Arc;
 
-use twilight_http::Client;
+use twilight_http::{Client, client::InteractionClient, response::marker::EmptyBody};
 use twilight_model::{
     application::interaction::modal::ModalInteractionData,
     gateway::payload::incoming::InteractionCreate,
+    guild::Guild,
+    http::interaction::InteractionResponse,
     id::{Id, marker::ApplicationMarker},
 };
 
 use super::Context;
-use crate::Metadata;
+use crate::{
+    Error, Metadata,
+    i18n::{Args, Catalogue, interaction_locales},
+};
 
 #[derive(Clone, Debug)]
 pub struct ModalContext<T: Clone + Send + Sync> {
@@ -19,6 +24,9 @@ pub struct ModalContext<T: Clone + Send + Sync> {
 
     pub event: InteractionCreate,
     pub data: ModalInteractionData,
+
+    /// messages of the handler's module, see [`crate::ModuleBuilder::locale`]
+    pub catalogue: Arc<Catalogue>,
 }
 
 impl<T: Clone + Send + Sync> ModalContext<T> {
@@ -36,6 +44,35 @@ impl<T: Clone + Send + Sync> ModalContext<T> {
             meta,
             data,
             event,
+
+            catalogue: Arc::default(),
         }
     }
+
+    /// `key` from the module's messages, in the user's or the guild's locale
+    pub fn t(&self, key: &str, args: &Args<'_>) -> String {
+        self.catalogue
+            .format(&interaction_locales(&self.event), key, args)
+    }
+
+    pub fn interaction(&self) -> InteractionClient<'_> {
+        self.client.interaction(self.application_id)
+    }
+
+    pub async fn guild(&self) -> Result<Option<Guild>, Error> {
+        let Some(guild_id) = self.event.guild_id else {
+            return Ok(None);
+        };
+
+        Ok(Some(self.client.guild(guild_id).await?.model().await?))
+    }
+
+    pub async fn response(
+        &self,
+        response: InteractionResponse,
+    ) -> Result<twilight_http::Response<EmptyBody>, twilight_http::Error> {
+        self.interaction()
+            .create_response(self.event.id, &self.event.token, &response)
+            .await
+    }
 }
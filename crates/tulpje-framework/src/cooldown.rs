@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use twilight_model::application::interaction::Interaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CooldownScope {
+    User,
+    Guild,
+    Channel,
+    Global,
+}
+
+/// allow `limit` uses of a command every `period` per `scope`
+///
+/// e.g. `Cooldown::per_guild(1, Duration::from_secs(30))` allows a command to be
+/// used once every 30 seconds in each guild
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cooldown {
+    pub limit: u32,
+    pub period: Duration,
+    pub scope: CooldownScope,
+}
+
+impl Cooldown {
+    pub fn new(limit: u32, period: Duration, scope: CooldownScope) -> Self {
+        Self {
+            limit,
+            period,
+            scope,
+        }
+    }
+
+    pub fn per_user(limit: u32, period: Duration) -> Self {
+        Self::new(limit, period, CooldownScope::User)
+    }
+
+    pub fn per_guild(limit: u32, period: Duration) -> Self {
+        Self::new(limit, period, CooldownScope::Guild)
+    }
+
+    pub fn per_channel(limit: u32, period: Duration) -> Self {
+        Self::new(limit, period, CooldownScope::Channel)
+    }
+
+    pub fn global(limit: u32, period: Duration) -> Self {
+        Self::new(limit, period, CooldownScope::Global)
+    }
+
+    /// identifier of the bucket an interaction counts towards, guild and channel
+    /// scoped cooldowns fall back to the user outside of guilds/channels
+    pub fn bucket(&self, interaction: &Interaction) -> String {
+        let user = || {
+            interaction
+                .author_id()
+                .map_or_else(|| "unknown".to_string(), |id| format!("user:{id}"))
+        };
+
+        match self.scope {
+            CooldownScope::User => user(),
+            CooldownScope::Guild => interaction
+                .guild_id
+                .map_or_else(user, |id| format!("guild:{id}")),
+            CooldownScope::Channel => interaction
+                .channel
+                .as_ref()
+                .map_or_else(user, |channel| format!("channel:{}", channel.id)),
+            CooldownScope::Global => "global".to_string(),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+const SEPARATOR: char = ':';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+}
+
+/// pattern for matching component `custom_id`s, segments are separated by `:`
+/// and `{name}` segments capture whatever is in that position
+///
+/// e.g. `emoji_stats:page:{page}:{sort}` matches `emoji_stats:page:2:count_desc`
+/// and captures `page = 2` and `sort = count_desc`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomIdPattern {
+    pattern: String,
+    segments: Vec<Segment>,
+}
+
+impl CustomIdPattern {
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split(SEPARATOR)
+            .map(|segment| {
+                segment
+                    .strip_prefix('{')
+                    .and_then(|s| s.strip_suffix('}'))
+                    .map_or_else(
+                        || Segment::Literal(segment.to_string()),
+                        |name| Segment::Param(name.to_string()),
+                    )
+            })
+            .collect();
+
+        Self {
+            pattern: pattern.to_string(),
+            segments,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// whether the pattern has no parameters and thus only matches itself
+    pub fn is_exact(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|segment| matches!(segment, Segment::Literal(_)))
+    }
+
+    /// match `custom_id` against the pattern, returning the captured parameters
+    pub fn captures(&self, custom_id: &str) -> Option<HashMap<String, String>> {
+        let parts: Vec<&str> = custom_id.split(SEPARATOR).collect();
+        if parts.len() != self.segments.len() {
+            return None;
+        }
+
+        let mut params = HashMap::new();
+        for (segment, part) in self.segments.iter().zip(parts) {
+            match segment {
+                Segment::Literal(literal) if literal == part => {}
+                Segment::Param(name) if !part.is_empty() => {
+                    params.insert(name.clone(), part.to_string());
+                }
+                _ => return None,
+            }
+        }
+
+        Some(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact() {
+        let pattern = CustomIdPattern::new("emoji_stats_sort");
+
+        assert!(pattern.is_exact());
+        assert_eq!(pattern.captures("emoji_stats_sort"), Some(HashMap::new()));
+        assert_eq!(pattern.captures("emoji_stats_sort:1"), None);
+        assert_eq!(pattern.captures("emoji_stats"), None);
+    }
+
+    #[test]
+    fn test_params() {
+        let pattern = CustomIdPattern::new("emoji_stats:page:{page}:{sort}");
+
+        assert!(!pattern.is_exact());
+        assert_eq!(
+            pattern.captures("emoji_stats:page:2:count_desc"),
+            Some(HashMap::from([
+                ("page".to_string(), "2".to_string()),
+                ("sort".to_string(), "count_desc".to_string()),
+            ]))
+        );
+        assert_eq!(pattern.captures("emoji_stats:page:2"), None);
+        assert_eq!(pattern.captures("emoji_stats:page::count_desc"), None);
+        assert_eq!(pattern.captures("emoji_stats:sort:2:count_desc"), None);
+    }
+}
@@ -0,0 +1,387 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore, mpsc},
+    task::JoinHandle,
+};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{Instrument as _, Span};
+use twilight_gateway::Event;
+use twilight_model::id::{
+    Id,
+    marker::{ChannelMarker, GuildMarker},
+};
+
+use crate::{Context, Error, Metadata, Registry, middleware::Middlewares};
+
+pub(crate) type EventMessage = (Metadata, Event, Option<Span>);
+
+/// whether events that belong together are handled one at a time, in the order
+/// they were received
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventOrdering {
+    /// handle every event as soon as there's room, in no particular order
+    #[default]
+    None,
+    /// events for the same guild are handled in order
+    Guild,
+    /// events for the same channel are handled in order, events without a
+    /// channel fall back to being ordered by guild
+    Channel,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DispatchOptions {
+    /// maximum amount of events being handled at once
+    pub concurrency: usize,
+    /// amount of events that can be queued before `Sender::send` waits, events
+    /// waiting on an earlier event for the same guild/channel are held back
+    /// separately, up to this amount too
+    pub queue_size: usize,
+    pub ordering: EventOrdering,
+}
+
+impl Default for DispatchOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 128,
+            queue_size: 1024,
+            ordering: EventOrdering::None,
+        }
+    }
+}
+
+/// what events are ordered by, guild and channel ids can be the same, e.g. a
+/// guild's default channel, so they're kept apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum OrderingKey {
+    Guild(Id<GuildMarker>),
+    Channel(Id<ChannelMarker>),
+}
+
+impl EventOrdering {
+    /// the key events are ordered by, `None` if the event can run in any order
+    fn key(self, event: &Event) -> Option<OrderingKey> {
+        match self {
+            Self::None => None,
+            Self::Guild => event.guild_id().map(OrderingKey::Guild),
+            Self::Channel => channel_id(event)
+                .map(OrderingKey::Channel)
+                .or_else(|| event.guild_id().map(OrderingKey::Guild)),
+        }
+    }
+}
+
+fn channel_id(event: &Event) -> Option<Id<ChannelMarker>> {
+    match event {
+        Event::ChannelCreate(channel) => Some(channel.id),
+        Event::ChannelDelete(channel) => Some(channel.id),
+        Event::ChannelUpdate(channel) => Some(channel.id),
+        Event::ChannelPinsUpdate(pins) => Some(pins.channel_id),
+        Event::InteractionCreate(interaction) => {
+            interaction.channel.as_ref().map(|channel| channel.id)
+        }
+        Event::MessageCreate(message) => Some(message.channel_id),
+        Event::MessageDelete(message) => Some(message.channel_id),
+        Event::MessageDeleteBulk(messages) => Some(messages.channel_id),
+        Event::MessageUpdate(message) => Some(message.channel_id),
+        Event::ReactionAdd(reaction) => Some(reaction.channel_id),
+        Event::ReactionRemove(reaction) => Some(reaction.channel_id),
+        Event::ReactionRemoveAll(reactions) => Some(reactions.channel_id),
+        Event::ReactionRemoveEmoji(reactions) => Some(reactions.channel_id),
+        Event::ThreadCreate(thread) => Some(thread.id),
+        Event::ThreadDelete(thread) => Some(thread.id),
+        Event::ThreadUpdate(thread) => Some(thread.id),
+        Event::TypingStart(typing) => Some(typing.channel_id),
+        _ => None,
+    }
+}
+
+pub(crate) struct DispatchHandle {
+    pub(crate) sender: mpsc::Sender<EventMessage>,
+    shutdown: CancellationToken,
+    handle: Option<JoinHandle<()>>,
+}
+impl DispatchHandle {
+    pub(crate) fn new<T: Clone + Send + Sync + 'static>(
+        registry: Arc<Registry<T>>,
+        ctx: Context<T>,
+        middleware: Arc<Middlewares<T>>,
+        options: DispatchOptions,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(options.queue_size);
+        let shutdown = CancellationToken::new();
+
+        let mut dispatch = Dispatch::new(
+            ctx,
+            registry,
+            middleware,
+            options,
+            receiver,
+            shutdown.child_token(),
+        );
+        let handle = Some(tokio::spawn(async move { dispatch.run().await }));
+
+        Self {
+            sender,
+            shutdown,
+            handle,
+        }
+    }
+
+    pub(crate) async fn send(
+        &mut self,
+        meta: Metadata,
+        event: Event,
+        span: Option<Span>,
+    ) -> Result<(), Box<mpsc::error::SendError<EventMessage>>> {
+        Ok(self.sender.send((meta, event, span)).await?)
+    }
+
+    pub(crate) fn shutdown(&mut self) {
+        self.shutdown.cancel();
+    }
+
+    pub(crate) async fn join(&mut self) -> Result<(), Error> {
+        Ok(self
+            .handle
+            .take()
+            .ok_or("Dispatch already shutdown")?
+            .await?)
+    }
+}
+
+type Lane = VecDeque<EventMessage>;
+
+struct Dispatch<T: Clone + Send + Sync> {
+    registry: Arc<Registry<T>>,
+    middleware: Arc<Middlewares<T>>,
+    ctx: Context<T>,
+    ordering: EventOrdering,
+
+    receiver: mpsc::Receiver<EventMessage>,
+    shutdown: CancellationToken,
+
+    permits: Arc<Semaphore>,
+    /// events that can run as soon as there's a permit, in order
+    ready: VecDeque<(Option<OrderingKey>, EventMessage)>,
+    /// events waiting on an earlier event with the same ordering key, a lane
+    /// exists for as long as an event with its key is ready or running
+    lanes: HashMap<OrderingKey, Lane>,
+    /// amount of events in all lanes
+    waiting: usize,
+    max_waiting: usize,
+    done_tx: mpsc::UnboundedSender<OrderingKey>,
+    done_rx: mpsc::UnboundedReceiver<OrderingKey>,
+
+    tracker: TaskTracker,
+}
+impl<T: Clone + Send + Sync + 'static> Dispatch<T> {
+    fn new(
+        ctx: Context<T>,
+        registry: Arc<Registry<T>>,
+        middleware: Arc<Middlewares<T>>,
+        options: DispatchOptions,
+
+        receiver: mpsc::Receiver<EventMessage>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        let (done_tx, done_rx) = mpsc::unbounded_channel();
+
+        Self {
+            registry,
+            middleware,
+            ctx,
+            ordering: options.ordering,
+
+            receiver,
+            shutdown,
+
+            permits: Arc::new(Semaphore::new(options.concurrency)),
+            ready: VecDeque::new(),
+            lanes: HashMap::new(),
+            waiting: 0,
+            max_waiting: options.queue_size,
+            done_tx,
+            done_rx,
+
+            tracker: TaskTracker::new(),
+        }
+    }
+
+    async fn run(&mut self) {
+        loop {
+            let permits = Arc::clone(&self.permits);
+            let has_ready = !self.ready.is_empty();
+            // only take events off the queue when the previous one could be
+            // started or put in its lane, so a full queue makes senders wait
+            let can_receive = !has_ready && self.waiting < self.max_waiting;
+
+            tokio::select! {
+                Some(key) = self.done_rx.recv() => self.next_in_lane(key),
+                Ok(permit) = permits.acquire_owned(), if has_ready => {
+                    if let Some((key, message)) = self.ready.pop_front() {
+                        self.spawn(permit, key, message);
+                    }
+                },
+                message = self.receiver.recv(), if can_receive => {
+                    let Some(message) = message else {
+                        break;
+                    };
+                    self.dispatch(message);
+                },
+                () = self.shutdown.cancelled() => break,
+            }
+        }
+
+        self.receiver.close();
+
+        // let events that were ready, or waiting on an earlier one in their
+        // lane, finish
+        while !self.ready.is_empty() || !self.lanes.is_empty() {
+            if let Some((key, message)) = self.ready.pop_front() {
+                let Ok(permit) = Arc::clone(&self.permits).acquire_owned().await else {
+                    break;
+                };
+                self.spawn(permit, key, message);
+                continue;
+            }
+
+            let Some(key) = self.done_rx.recv().await else {
+                break;
+            };
+            self.next_in_lane(key);
+        }
+
+        self.tracker.close();
+        self.tracker.wait().await;
+    }
+
+    fn dispatch(&mut self, message: EventMessage) {
+        let Some(key) = self.ordering.key(&message.1) else {
+            self.ready.push_back((None, message));
+            return;
+        };
+
+        if let Some(lane) = self.lanes.get_mut(&key) {
+            lane.push_back(message);
+            self.waiting += 1;
+            return;
+        }
+
+        self.lanes.insert(key, Lane::new());
+        self.ready.push_back((Some(key), message));
+    }
+
+    /// an event in the lane for `key` finished, make the next one ready or
+    /// close the lane
+    fn next_in_lane(&mut self, key: OrderingKey) {
+        let next = self.lanes.get_mut(&key).and_then(VecDeque::pop_front);
+        match next {
+            Some(message) => {
+                self.waiting -= 1;
+                self.ready.push_back((Some(key), message));
+            }
+            None => {
+                self.lanes.remove(&key);
+            }
+        }
+    }
+
+    fn spawn(
+        &self,
+        permit: OwnedSemaphorePermit,
+        key: Option<OrderingKey>,
+        (meta, event, span): EventMessage,
+    ) {
+        let registry = Arc::clone(&self.registry);
+        let middleware = Arc::clone(&self.middleware);
+        let ctx = self.ctx.clone();
+        let done_tx = self.done_tx.clone();
+
+        self.tracker.spawn(async move {
+            crate::handle(meta, ctx, &registry, &middleware, event)
+                .instrument(span.unwrap_or(Span::none()))
+                .await;
+            drop(permit);
+
+            if let Some(key) = key
+                && let Err(err) = done_tx.send(key)
+            {
+                tracing::warn!("couldn't notify dispatcher event finished: {err}");
+            }
+        });
+    }
+}
+
+pub struct Sender {
+    pub(crate) sender: mpsc::Sender<EventMessage>,
+}
+
+impl Sender {
+    /// queue an event, waits while the queue is full
+    pub async fn send(
+        &self,
+        meta: Metadata,
+        event: Event,
+    ) -> Result<(), Box<mpsc::error::SendError<EventMessage>>> {
+        Ok(self.sender.send((meta, event, None)).await?)
+    }
+
+    pub async fn with_span(
+        &self,
+        meta: Metadata,
+        event: Event,
+        span: Span,
+    ) -> Result<(), Box<mpsc::error::SendError<EventMessage>>> {
+        Ok(self.sender.send((meta, event, Some(span))).await?)
+    }
+
+    pub fn closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::gateway::payload::incoming::TypingStart;
+
+    use super::*;
+
+    #[test]
+    fn test_ordering_key() {
+        let event = Event::TypingStart(Box::new(TypingStart {
+            channel_id: Id::new(1),
+            guild_id: Some(Id::new(1)),
+            member: None,
+            timestamp: 0,
+            user_id: Id::new(2),
+        }));
+
+        assert_eq!(
+            EventOrdering::Guild.key(&event),
+            Some(OrderingKey::Guild(Id::new(1))),
+            "guild ordering should key on the guild"
+        );
+        assert_eq!(
+            EventOrdering::Channel.key(&event),
+            Some(OrderingKey::Channel(Id::new(1))),
+            "channel ordering should key on the channel"
+        );
+        assert_ne!(
+            EventOrdering::Guild.key(&event),
+            EventOrdering::Channel.key(&event),
+            "guilds and channels with the same id shouldn't share a key"
+        );
+        assert_eq!(
+            EventOrdering::None.key(&event),
+            None,
+            "no ordering shouldn't key events"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt::Display;
+
+use twilight_model::channel::message::Component;
+use twilight_util::builder::message::{ContainerBuilder, TextDisplayBuilder};
+
+use crate::{Error, Metadata, color};
+
+/// errors handlers can return (converted into [`Error`]) to tell the user what
+/// went wrong, anything that isn't a `CommandError` is treated as `Internal`
+#[derive(Debug)]
+pub enum CommandError {
+    /// the user did something wrong, e.g. passed an invalid argument
+    User(String),
+    /// the user or bot lacks the permissions to do this
+    MissingPermissions(String),
+    /// something the user referred to doesn't exist
+    NotFound(String),
+    /// something broke, only this variant gets logged and shows an error code
+    Internal(Error),
+}
+
+impl CommandError {
+    pub fn user(message: impl Into<String>) -> Self {
+        Self::User(message.into())
+    }
+
+    pub fn missing_permissions(message: impl Into<String>) -> Self {
+        Self::MissingPermissions(message.into())
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound(message.into())
+    }
+
+    /// downcast a handler error, wrapping anything else as `Internal`
+    pub fn from_error(err: Error) -> Self {
+        match err.downcast::<Self>() {
+            Ok(err) => *err,
+            Err(err) => Self::Internal(err),
+        }
+    }
+
+    pub fn is_internal(&self) -> bool {
+        matches!(self, Self::Internal(_))
+    }
+
+    /// the message shown to the user for this error, `extra_message` is shown
+    /// with internal errors
+    pub fn render(&self, meta: &Metadata, extra_message: &str) -> Component {
+        let (color, text) = match self {
+            Self::User(message) => (color::roles::RED, format!("### Error\n{message}")),
+            Self::MissingPermissions(message) => (
+                color::roles::ORANGE,
+                format!("### Missing Permissions\n{message}"),
+            ),
+            Self::NotFound(message) => (color::roles::RED, format!("### Not Found\n{message}")),
+            Self::Internal(_) => (
+                color::roles::RED,
+                format!(
+                    "### Internal Error\n{}\n**Error Code**\n```{}```",
+                    extra_message, meta.uuid
+                ),
+            ),
+        };
+
+        ContainerBuilder::new()
+            .accent_color(Some(*color))
+            .component(TextDisplayBuilder::new(text).build())
+            .build()
+            .into()
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::User(message) => write!(f, "user error: {message}"),
+            Self::MissingPermissions(message) => write!(f, "missing permissions: {message}"),
+            Self::NotFound(message) => write!(f, "not found: {message}"),
+            Self::Internal(err) => write!(f, "internal error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Internal(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_error() {
+        let err: Error = CommandError::not_found("system `abcde`").into();
+        assert!(
+            matches!(CommandError::from_error(err), CommandError::NotFound(message) if message == "system `abcde`"),
+            "CommandError should survive being boxed"
+        );
+
+        let err: Error = "database down".into();
+        assert!(
+            CommandError::from_error(err).is_internal(),
+            "other errors should be internal"
+        );
+    }
+}
@@ -1,20 +1,25 @@
 use std::{future::Future, pin::Pin, sync::Arc};
 
-use tokio::{sync::mpsc, task::JoinHandle};
-use tracing::{Instrument as _, Span};
+use tokio::sync::mpsc;
+use tracing::Span;
 
 use crate::Metadata;
-use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use twilight_gateway::Event;
 use twilight_http::Client;
 use twilight_model::id::{Id, marker::ApplicationMarker};
 
+pub use crate::dispatch::{DispatchOptions, EventOrdering, Sender};
+pub use crate::scheduler::SchedulerSender;
+
+use crate::context::ErrorMessageFunc;
+use crate::dispatch::{DispatchHandle, EventMessage};
 use crate::handler::task_handler::TaskHandler;
+use crate::job::{JobRunnerHandle, Jobs};
+use crate::middleware::{Middleware, Middlewares};
 use crate::scheduler::{SchedulerHandle, SchedulerTaskMessage};
 use crate::{Context, Error, Registry};
 
 type SetupFunc<T> = fn(ctx: Context<T>) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
-type EventMessage = (Metadata, Event, Option<Span>);
 
 #[derive(Clone)]
 pub struct FrameworkBuilder<T: Clone + Send + Sync> {
@@ -24,6 +29,10 @@ pub struct FrameworkBuilder<T: Clone + Send + Sync> {
     user_data: Arc<T>,
 
     setup_fn: Option<SetupFunc<T>>,
+    middleware: Vec<Arc<dyn Middleware<T>>>,
+    dispatch: DispatchOptions,
+    jobs: Option<Jobs>,
+    error_message: Option<ErrorMessageFunc<T>>,
 }
 
 impl<T: Clone + Send + Sync + 'static> FrameworkBuilder<T> {
@@ -39,6 +48,10 @@ impl<T: Clone + Send + Sync + 'static> FrameworkBuilder<T> {
             app_id,
             user_data: Arc::new(user_data),
             setup_fn: None,
+            middleware: Vec::new(),
+            dispatch: DispatchOptions::default(),
+            jobs: None,
+            error_message: None,
         }
     }
 
@@ -47,6 +60,32 @@ impl<T: Clone + Send + Sync + 'static> FrameworkBuilder<T> {
         self
     }
 
+    /// add a middleware layer, layers run in the order they're added
+    pub fn middleware(&mut self, middleware: impl Middleware<T> + 'static) -> &mut Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// concurrency limit, queue size and ordering of event handling
+    pub fn dispatch(&mut self, options: DispatchOptions) -> &mut Self {
+        self.dispatch = options;
+        self
+    }
+
+    /// run one-off jobs from this store, without it jobs can be scheduled but
+    /// won't run on this instance
+    pub fn jobs(&mut self, jobs: Jobs) -> &mut Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// extra message shown to users with internal errors, e.g. where to report
+    /// them, called every time so it can change at runtime
+    pub fn error_message(&mut self, func: ErrorMessageFunc<T>) -> &mut Self {
+        self.error_message = Some(func);
+        self
+    }
+
     pub fn build(&self) -> Framework<T> {
         Framework::new(
             Arc::clone(&self.registry),
@@ -54,6 +93,10 @@ impl<T: Clone + Send + Sync + 'static> FrameworkBuilder<T> {
             self.app_id,
             Arc::clone(&self.user_data),
             self.setup_fn,
+            self.middleware.clone(),
+            self.dispatch,
+            self.jobs.clone(),
+            self.error_message,
         )
     }
 }
@@ -64,24 +107,46 @@ pub struct Framework<T: Clone + Send + Sync> {
 
     scheduler: SchedulerHandle<T>,
     dispatcher: DispatchHandle,
+    jobs: Option<JobRunnerHandle>,
 }
 
 impl<T: Clone + Send + Sync + 'static> Framework<T> {
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "called by FrameworkBuilder, which is what should be used"
+    )]
     pub fn new(
         registry: Arc<Registry<T>>,
         client: Arc<Client>,
         application_id: Id<ApplicationMarker>,
         services: Arc<T>,
         setup_fn: Option<SetupFunc<T>>,
+        middleware: Vec<Arc<dyn Middleware<T>>>,
+        dispatch: DispatchOptions,
+        jobs: Option<Jobs>,
+        error_message: Option<ErrorMessageFunc<T>>,
     ) -> Self {
         let ctx = Context {
             application_id,
             services,
             client,
+            error_message,
         };
-        let scheduler =
-            SchedulerHandle::new(registry.tasks.values().cloned().collect(), ctx.clone());
-        let dispatcher = DispatchHandle::new(registry, ctx.clone());
+        let middleware = Arc::new(Middlewares::new(middleware));
+        let scheduler = SchedulerHandle::new(
+            registry.tasks.values().cloned().collect(),
+            ctx.clone(),
+            Arc::clone(&middleware),
+        );
+        let jobs = jobs.map(|jobs| {
+            JobRunnerHandle::new(
+                jobs,
+                Arc::clone(&registry),
+                ctx.clone(),
+                Arc::clone(&middleware),
+            )
+        });
+        let dispatcher = DispatchHandle::new(registry, ctx.clone(), middleware, dispatch);
 
         Self {
             ctx,
@@ -89,6 +154,7 @@ impl<T: Clone + Send + Sync + 'static> Framework<T> {
 
             scheduler,
             dispatcher,
+            jobs,
         }
     }
 
@@ -120,149 +186,41 @@ impl<T: Clone + Send + Sync + 'static> Framework<T> {
         self.scheduler.disable_task(name)
     }
 
+    /// handle for controlling scheduled tasks from outside the framework
+    pub fn scheduler(&self) -> SchedulerSender<T> {
+        self.scheduler.sender()
+    }
+
     pub fn sender(&self) -> Sender {
         Sender {
             sender: self.dispatcher.sender.clone(),
         }
     }
 
-    pub fn send(
+    pub async fn send(
         &mut self,
         meta: Metadata,
         event: Event,
         span: Option<Span>,
     ) -> Result<(), Box<mpsc::error::SendError<EventMessage>>> {
-        self.dispatcher.send(meta, event, span)
+        self.dispatcher.send(meta, event, span).await
     }
 
     pub async fn shutdown(&mut self) {
         self.scheduler.shutdown();
         self.dispatcher.shutdown();
+        if let Some(jobs) = &mut self.jobs {
+            jobs.shutdown();
+        }
     }
 
     pub async fn join(&mut self) -> Result<(), Error> {
         self.scheduler.join().await?;
         self.dispatcher.join().await?;
-
-        Ok(())
-    }
-}
-
-struct DispatchHandle {
-    sender: mpsc::UnboundedSender<EventMessage>,
-    shutdown: CancellationToken,
-    handle: Option<JoinHandle<()>>,
-}
-impl DispatchHandle {
-    fn new<T: Clone + Send + Sync + 'static>(registry: Arc<Registry<T>>, ctx: Context<T>) -> Self {
-        let (sender, receiver) = mpsc::unbounded_channel();
-        let shutdown = CancellationToken::new();
-
-        let mut dispatch = Dispatch::new(ctx, registry, receiver, shutdown.child_token());
-        let handle = Some(tokio::spawn(async move { dispatch.run().await }));
-
-        Self {
-            sender,
-            shutdown,
-            handle,
-        }
-    }
-
-    fn send(
-        &mut self,
-        meta: Metadata,
-        event: Event,
-        span: Option<Span>,
-    ) -> Result<(), Box<mpsc::error::SendError<EventMessage>>> {
-        Ok(self.sender.send((meta, event, span))?)
-    }
-
-    fn shutdown(&mut self) {
-        self.shutdown.cancel();
-    }
-
-    async fn join(&mut self) -> Result<(), Error> {
-        Ok(self
-            .handle
-            .take()
-            .ok_or("Dispatch already shutdown")?
-            .await?)
-    }
-}
-
-struct Dispatch<T: Clone + Send + Sync> {
-    registry: Arc<Registry<T>>,
-    ctx: Context<T>,
-
-    receiver: mpsc::UnboundedReceiver<EventMessage>,
-    shutdown: CancellationToken,
-
-    tracker: TaskTracker,
-}
-impl<T: Clone + Send + Sync + 'static> Dispatch<T> {
-    fn new(
-        ctx: Context<T>,
-        registry: Arc<Registry<T>>,
-
-        receiver: mpsc::UnboundedReceiver<EventMessage>,
-        shutdown: CancellationToken,
-    ) -> Self {
-        Self {
-            registry,
-            ctx,
-
-            receiver,
-            shutdown,
-
-            tracker: TaskTracker::new(),
+        if let Some(jobs) = &mut self.jobs {
+            jobs.join().await?;
         }
-    }
 
-    async fn run(&mut self) {
-        loop {
-            tokio::select! {
-                Some((meta, event, span)) = self.receiver.recv() => {
-                    let registry = Arc::clone(&self.registry);
-                    let ctx = self.ctx.clone();
-
-                    self.tracker.spawn(async move {
-                        crate::handle(meta, ctx, &registry, event).instrument(span.unwrap_or(Span::none())).await;
-                    });
-                },
-                () = self.shutdown.cancelled() => break,
-            }
-        }
-
-        self.receiver.close();
-        self.tracker.close();
-
-        self.tracker.wait().await;
-    }
-}
-
-pub struct Sender {
-    sender: mpsc::UnboundedSender<EventMessage>,
-}
-
-impl Sender {
-    pub fn send(
-        &self,
-        meta: Metadata,
-        event: Event,
-    ) -> Result<(), Box<mpsc::error::SendError<EventMessage>>> {
-        Ok(self.sender.send((meta, event, None))?)
-    }
-
-    pub fn with_span(
-        &self,
-        meta: Metadata,
-        event: Event,
-        span: Span,
-    ) -> Result<(), Box<mpsc::error::SendError<EventMessage>>> {
-        Ok(self.sender.send((meta, event, Some(span)))?)
-    }
-
-    pub fn closed(&self) -> bool {
-        self.sender.is_closed()
+        Ok(())
     }
 }
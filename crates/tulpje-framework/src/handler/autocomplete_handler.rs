@@ -1 +1,22 @@
+use std::{future::Future, pin::Pin};
 
+use super::super::context::AutocompleteContext;
+use crate::Error;
+
+pub(crate) type AutocompleteFunc<T> =
+    fn(AutocompleteContext<T>) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+#[derive(Clone)]
+pub struct AutocompleteHandler<T: Clone + Send + Sync> {
+    pub module: String,
+    pub command: String,
+    pub option: String,
+    pub func: AutocompleteFunc<T>,
+}
+
+impl<T: Clone + Send + Sync> AutocompleteHandler<T> {
+    pub async fn run(&self, ctx: AutocompleteContext<T>) -> Result<(), Error> {
+        // can add more handling/parsing/etc here in the future
+        (self.func)(ctx).await
+    }
+}
@@ -1,11 +1,15 @@
 use std::{future::Future, pin::Pin};
 
-use twilight_model::channel::message::MessageFlags;
-use twilight_util::builder::message::{ContainerBuilder, TextDisplayBuilder};
+use twilight_model::{
+    channel::message::MessageFlags,
+    guild::Permissions,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
 
-use super::super::context::CommandContext;
+use super::super::context::{Acknowledgement, CommandContext};
 
-use crate::{Error, color};
+use crate::{Error, cooldown::Cooldown, error::CommandError};
 
 pub(crate) type CommandFunc<T> =
     fn(CommandContext<T>) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
@@ -15,39 +19,72 @@ pub struct CommandHandler<T: Clone + Send + Sync> {
     pub module: String,
     pub name: String,
     pub func: CommandFunc<T>,
+    pub cooldown: Option<Cooldown>,
+    /// checked by middleware before the handler runs
+    pub bot_permissions: Permissions,
+    /// checked by middleware, see [`crate::module::command_builder::CommandBuilder::owner_only`]
+    pub owner_only: bool,
 }
 
 impl<T: Clone + Send + Sync> CommandHandler<T> {
     pub async fn run(&self, ctx: CommandContext<T>) -> Result<(), Error> {
-        // TODO: More elegant way of handling command errors
-        // TODO: Test if errors work in DMs
-        if let Err(err) = (self.func)(ctx.clone()).await {
+        // can add more handling/parsing/etc here in the future
+        (self.func)(ctx).await
+    }
+
+    /// inform the user that something went wrong while running the command,
+    /// only internal errors get logged and show an error code
+    pub async fn report_error(
+        &self,
+        ctx: &CommandContext<T>,
+        err: Error,
+        extra_message: &str,
+    ) -> Result<(), Error> {
+        let err = CommandError::from_error(err);
+        if err.is_internal() {
             tracing::error!(
                 "error during command {}, sending reference to client: {}",
                 self.name,
                 err
             );
+        } else {
+            tracing::debug!("command {} failed: {}", self.name, err);
+        }
 
-            if let Some(chan) = &ctx.event.channel {
-                ctx.client
-                    .create_message(chan.id)
-                    .flags(MessageFlags::IS_COMPONENTS_V2)
-                    .components(&[ContainerBuilder::new()
-                        .accent_color(Some(*color::roles::RED))
-                        .component(
-                            // TODO: Better way to handle extra error info than, whatever this is
-                            TextDisplayBuilder::new(format!(
-                                "### Internal Error\n{}\n**Error Code**\n```{}```",
-                                std::env::var("TULPJE_EXTRA_ERROR_MESSAGE").unwrap_or_default(),
-                                ctx.meta.uuid
-                            ))
+        let component = err.render(&ctx.meta, extra_message);
+        let flags = MessageFlags::EPHEMERAL | MessageFlags::IS_COMPONENTS_V2;
+
+        match ctx.acknowledgement() {
+            Acknowledgement::None => {
+                ctx.response(InteractionResponse {
+                    kind: InteractionResponseType::ChannelMessageWithSource,
+                    data: Some(
+                        InteractionResponseDataBuilder::new()
+                            .flags(flags)
+                            .components([component])
                             .build(),
-                        )
-                        .build()
-                        .into()])
+                    ),
+                })
+                .await?;
+            }
+            // the first followup after a defer replaces the loading message and
+            // ignores EPHEMERAL, so edit it directly, it keeps the visibility
+            // chosen when deferring
+            Acknowledgement::Deferred => {
+                ctx.interaction()
+                    .update_response(&ctx.event.token)
+                    .content(None)
+                    .embeds(None)
+                    .flags(MessageFlags::IS_COMPONENTS_V2)
+                    .components(Some(&[component]))
+                    .await?;
+            }
+            Acknowledgement::Responded => {
+                ctx.interaction()
+                    .create_followup(&ctx.event.token)
+                    .flags(flags)
+                    .components(&[component])
                     .await?;
-            } else {
-                tracing::warn!(event = ?ctx.event, "channel on event was empty, can't send error");
             }
         }
 
@@ -1,7 +1,7 @@
 use std::{future::Future, pin::Pin};
 
 use super::super::context::ComponentInteractionContext;
-use crate::Error;
+use crate::{Error, custom_id::CustomIdPattern};
 
 pub(crate) type ComponentInteractionFunc<T> =
     fn(ComponentInteractionContext<T>) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
@@ -9,7 +9,7 @@ pub(crate) type ComponentInteractionFunc<T> =
 #[derive(Clone)]
 pub struct ComponentInteractionHandler<T: Clone + Send + Sync> {
     pub module: String,
-    pub custom_id: String,
+    pub custom_id: CustomIdPattern,
     pub func: ComponentInteractionFunc<T>,
 }
 
@@ -19,3 +19,18 @@ impl<T: Clone + Send + Sync> ComponentInteractionHandler<T> {
         (self.func)(ctx).await
     }
 }
+
+/// add `handler` after the existing handlers, or replace the handler with the
+/// same pattern in place, keeping its precedence
+pub(crate) fn insert_component<T: Clone + Send + Sync>(
+    handlers: &mut Vec<ComponentInteractionHandler<T>>,
+    handler: ComponentInteractionHandler<T>,
+) {
+    match handlers
+        .iter_mut()
+        .find(|existing| existing.custom_id == handler.custom_id)
+    {
+        Some(existing) => *existing = handler,
+        None => handlers.push(handler),
+    }
+}
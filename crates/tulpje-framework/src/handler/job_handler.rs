@@ -0,0 +1,21 @@
+use std::{future::Future, pin::Pin};
+
+use crate::Error;
+use crate::context::JobContext;
+
+pub(crate) type JobFunc<T> =
+    fn(JobContext<T>) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+#[derive(Clone)]
+pub struct JobHandler<T: Clone + Send + Sync> {
+    pub module: String,
+    pub name: String,
+    pub func: JobFunc<T>,
+}
+
+impl<T: Clone + Send + Sync> JobHandler<T> {
+    pub async fn run(&self, ctx: JobContext<T>) -> Result<(), Error> {
+        // can add more handling/parsing/etc here in the future
+        (self.func)(ctx).await
+    }
+}
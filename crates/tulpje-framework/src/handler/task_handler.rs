@@ -1,7 +1,16 @@
-use std::{future::Future, pin::Pin};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use async_cron_scheduler::cron::Schedule;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 use crate::Error;
 use crate::context::TaskContext;
@@ -9,15 +18,86 @@ use crate::context::TaskContext;
 pub(crate) type TaskFunc<T> =
     fn(TaskContext<T>) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
 
+/// what to do when a task is scheduled while its previous run is still going
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TaskOverlap {
+    /// don't run the task this time
+    #[default]
+    Skip,
+    /// run the task once the previous run finished
+    Queue,
+    /// run the task alongside the previous run
+    Allow,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskStats {
+    pub module: String,
+    pub name: String,
+
+    /// unix timestamps in seconds
+    pub last_start: Option<i64>,
+    pub last_finish: Option<i64>,
+    pub last_duration_ms: Option<u64>,
+    pub last_error: Option<String>,
+
+    pub running: u32,
+    pub runs: u64,
+    pub failures: u64,
+    pub skipped: u64,
+}
+
+/// state shared between all clones of a `TaskHandler`
+#[derive(Default)]
+pub(crate) struct TaskState {
+    pub(crate) stats: Mutex<TaskStats>,
+    pub(crate) lock: tokio::sync::Mutex<()>,
+    /// disabled through the scheduler after it was started
+    pub(crate) paused: AtomicBool,
+}
+
 #[derive(Clone)]
+#[expect(
+    clippy::partial_pub_fields,
+    reason = "the run state is shared between clones and only managed by the scheduler"
+)]
 pub struct TaskHandler<T: Clone + Send + Sync> {
     pub module: String,
     pub name: String,
     pub cron: Schedule,
+    pub overlap: TaskOverlap,
     pub func: TaskFunc<T>,
+
+    pub(crate) state: Arc<TaskState>,
 }
 
 impl<T: Clone + Send + Sync> TaskHandler<T> {
+    pub(crate) fn new(
+        module: String,
+        name: String,
+        cron: Schedule,
+        overlap: TaskOverlap,
+        func: TaskFunc<T>,
+    ) -> Self {
+        let state = TaskState {
+            stats: Mutex::new(TaskStats {
+                module: module.clone(),
+                name: name.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        Self {
+            module,
+            name,
+            cron,
+            overlap,
+            func,
+            state: Arc::new(state),
+        }
+    }
+
     pub async fn run(&self, ctx: TaskContext<T>) -> Result<(), Error> {
         // can add more handling/parsing/etc here in the future
         (self.func)(ctx).await
@@ -26,4 +106,44 @@ impl<T: Clone + Send + Sync> TaskHandler<T> {
     pub fn next_run(&self) -> Option<DateTime<Utc>> {
         self.cron.upcoming(Utc).next()
     }
+
+    /// whether the task was disabled at runtime, see [`crate::scheduler::SchedulerSender`]
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn stats(&self) -> TaskStats {
+        self.state
+            .stats
+            .lock()
+            .expect("task stats lock poisoned")
+            .clone()
+    }
+
+    pub(crate) fn record_start(&self) {
+        let mut stats = self.state.stats.lock().expect("task stats lock poisoned");
+        stats.last_start = Some(Utc::now().timestamp());
+        stats.running += 1;
+    }
+
+    pub(crate) fn record_skip(&self) {
+        self.state
+            .stats
+            .lock()
+            .expect("task stats lock poisoned")
+            .skipped += 1;
+    }
+
+    pub(crate) fn record_finish(&self, elapsed: Duration, result: &Result<(), Error>) {
+        let mut stats = self.state.stats.lock().expect("task stats lock poisoned");
+        stats.last_finish = Some(Utc::now().timestamp());
+        stats.last_duration_ms = Some(u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
+        stats.running = stats.running.saturating_sub(1);
+        stats.runs += 1;
+
+        if let Err(err) = result {
+            stats.failures += 1;
+            stats.last_error = Some(err.to_string());
+        }
+    }
 }
@@ -0,0 +1,341 @@
+use std::{collections::HashMap, fmt::Display};
+
+use twilight_model::{
+    application::command::{Command, CommandOption},
+    gateway::payload::incoming::InteractionCreate,
+};
+
+/// locale of the plain strings passed to builders, and the fallback for
+/// messages missing in other locales
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+pub type Args<'a> = [(&'a str, &'a dyn Display)];
+
+/// messages per locale, loaded from a subset of the fluent syntax
+///
+/// ```ftl
+/// # comment
+/// stats-title = Emotes in { $guild }
+/// cmd-emoji-stats =
+///     .name = stats
+///     .description = Show emoji usage stats
+/// ```
+///
+/// attributes are stored as `message.attribute`, and only `{ $variable }`
+/// placeables are supported
+#[derive(Debug, Clone, Default)]
+pub struct Catalogue {
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Catalogue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// parse `source` and add its messages to `locale`, messages that already
+    /// exist are overwritten
+    pub fn add_locale(&mut self, locale: &str, source: &str) -> Result<(), ParseError> {
+        let messages = parse(source)?;
+        self.locales
+            .entry(locale.to_string())
+            .or_default()
+            .extend(messages);
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locales.is_empty()
+    }
+
+    /// find `key` in the first locale that has it, falling back to
+    /// [`DEFAULT_LOCALE`] and finally the key itself
+    pub fn get<'a>(&'a self, locales: &[&str], key: &'a str) -> &'a str {
+        locales
+            .iter()
+            .chain(&[DEFAULT_LOCALE])
+            .find_map(|locale| self.locales.get(*locale)?.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// like [`Catalogue::get`], with `{ $name }` placeables replaced by `args`
+    pub fn format(&self, locales: &[&str], key: &str, args: &Args<'_>) -> String {
+        format_message(self.get(locales, key), args)
+    }
+
+    /// `key` in every locale except the default one, in the format discord
+    /// expects for command localizations
+    pub fn localizations(&self, key: &str) -> Option<HashMap<String, String>> {
+        let localizations: HashMap<String, String> = self
+            .locales
+            .iter()
+            .filter(|(locale, _)| *locale != DEFAULT_LOCALE)
+            .filter_map(|(locale, messages)| Some((locale.clone(), messages.get(key)?.clone())))
+            .collect();
+
+        (!localizations.is_empty()).then_some(localizations)
+    }
+
+    /// fill in the name and description localizations of `command` and its
+    /// options, from the `.name` and `.description` attributes of messages
+    /// named `cmd-` followed by the command, subcommand and option names
+    /// joined by `-`, e.g. `cmd-emoji-stats-sort`
+    pub fn localize_command(&self, command: &mut Command) {
+        let key = format!("cmd-{}", command.name);
+
+        if let Some(names) = self.localizations(&format!("{key}.name")) {
+            command.name_localizations = Some(names);
+        }
+        if let Some(descriptions) = self.localizations(&format!("{key}.description")) {
+            command.description_localizations = Some(descriptions);
+        }
+
+        for option in &mut command.options {
+            self.localize_option(&key, option);
+        }
+    }
+
+    fn localize_option(&self, parent: &str, option: &mut CommandOption) {
+        let key = format!("{parent}-{}", option.name);
+
+        if let Some(names) = self.localizations(&format!("{key}.name")) {
+            option.name_localizations = Some(names);
+        }
+        if let Some(descriptions) = self.localizations(&format!("{key}.description")) {
+            option.description_localizations = Some(descriptions);
+        }
+
+        for option in option.options.iter_mut().flatten() {
+            self.localize_option(&key, option);
+        }
+    }
+}
+
+/// locales to look messages up in for an interaction, the user's locale first
+/// then the guild's
+pub fn interaction_locales(event: &InteractionCreate) -> Vec<&str> {
+    [event.locale.as_deref(), event.guild_locale.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn parse(source: &str) -> Result<HashMap<String, String>, ParseError> {
+    let mut messages = HashMap::new();
+    // the message or attribute continuation lines get appended to
+    let mut current: Option<String> = None;
+    let mut message: Option<String> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let error = |message: &str| ParseError {
+            line: index + 1,
+            message: message.into(),
+        };
+
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            let line = line.trim();
+
+            if let Some(attribute) = line.strip_prefix('.') {
+                let parent = message
+                    .as_ref()
+                    .ok_or_else(|| error("attribute without a message"))?;
+                let (name, value) =
+                    split_entry(attribute).ok_or_else(|| error("expected `.attribute = value`"))?;
+
+                let key = format!("{parent}.{name}");
+                messages.insert(key.clone(), value.to_string());
+                current = Some(key);
+            } else {
+                let key = current
+                    .as_ref()
+                    .ok_or_else(|| error("continuation line without a message"))?;
+                let Some(value) = messages.get_mut(key) else {
+                    return Err(error("continuation line without a message"));
+                };
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(line);
+            }
+
+            continue;
+        }
+
+        let (name, value) = split_entry(line).ok_or_else(|| error("expected `name = value`"))?;
+        messages.insert(name.to_string(), value.to_string());
+        current = Some(name.to_string());
+        message = Some(name.to_string());
+    }
+
+    // messages that only exist to hold attributes have no value of their own
+    messages.retain(|_, value| !value.is_empty());
+
+    Ok(messages)
+}
+
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim();
+
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    valid.then(|| (name, value.trim()))
+}
+
+fn format_message(message: &str, args: &Args<'_>) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some((before, after)) = rest.split_once('{') {
+        result.push_str(before);
+
+        let Some((placeable, after)) = after.split_once('}') else {
+            result.push('{');
+            rest = after;
+            break;
+        };
+
+        let value = placeable
+            .trim()
+            .strip_prefix('$')
+            .and_then(|name| args.iter().find(|(arg, _)| *arg == name));
+        match value {
+            Some((_, value)) => result.push_str(&value.to_string()),
+            // leave unknown placeables as-is, so missing arguments are obvious
+            None => result.push_str(&format!("{{{placeable}}}")),
+        }
+
+        rest = after;
+    }
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::application::command::CommandType;
+
+    use super::*;
+    use crate::module::command_builder::{CommandBuilder, SubCommandBuilder};
+
+    const EN: &str = "
+# comment
+greeting = Hello { $name }!
+multiline = first
+    second
+cmd-emoji =
+    .description = emoji commands
+";
+    const NL: &str = "
+greeting = Hallo { $name }!
+cmd-emoji =
+    .description = emoji commando's
+cmd-emoji-stats =
+    .name = statistieken
+    .description = emoji statistieken
+";
+
+    fn catalogue() -> Catalogue {
+        let mut catalogue = Catalogue::new();
+        catalogue
+            .add_locale(DEFAULT_LOCALE, EN)
+            .expect("couldn't parse en-US");
+        catalogue.add_locale("nl", NL).expect("couldn't parse nl");
+        catalogue
+    }
+
+    #[test]
+    fn test_format() {
+        let catalogue = catalogue();
+
+        assert_eq!(
+            catalogue.format(&["nl"], "greeting", &[("name", &"Tulpje")]),
+            "Hallo Tulpje!",
+            "message should be formatted in the requested locale"
+        );
+        assert_eq!(
+            catalogue.format(&["fr"], "greeting", &[("name", &42)]),
+            "Hello 42!",
+            "unknown locales should fall back to the default locale"
+        );
+        assert_eq!(
+            catalogue.format(&["nl"], "greeting", &[]),
+            "Hallo { $name }!",
+            "missing arguments should be left as-is"
+        );
+        assert_eq!(
+            catalogue.get(&["nl"], "multiline"),
+            "first\nsecond",
+            "continuation lines should be joined"
+        );
+        assert_eq!(
+            catalogue.get(&[], "missing"),
+            "missing",
+            "missing messages should fall back to the key"
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            Catalogue::new().add_locale("nl", "greeting = hi\n  .attr"),
+            Err(ParseError {
+                line: 2,
+                message: "expected `.attribute = value`".into()
+            }),
+            "invalid attribute should be an error"
+        );
+        assert!(
+            Catalogue::new().add_locale("nl", "  .attr = hi").is_err(),
+            "attribute without a message should be an error"
+        );
+    }
+
+    #[test]
+    fn test_localize_command() {
+        let mut command = CommandBuilder::<()>::new("emoji", "emoji", CommandType::ChatInput)
+            .subcommand(SubCommandBuilder::new("stats", "emoji stats"))
+            .build();
+        catalogue().localize_command(&mut command);
+
+        assert_eq!(
+            command.description_localizations,
+            Some(HashMap::from([("nl".into(), "emoji commando's".into())])),
+            "command description should be localized"
+        );
+        assert_eq!(
+            command.name_localizations, None,
+            "command name isn't translated"
+        );
+
+        let subcommand = command.options.first().expect("command has a subcommand");
+        assert_eq!(
+            subcommand.name_localizations,
+            Some(HashMap::from([("nl".into(), "statistieken".into())])),
+            "subcommand name should be localized"
+        );
+    }
+}
@@ -1,12 +1,12 @@
 use twilight_model::{
     application::interaction::{
-        InteractionData,
+        InteractionData, InteractionType,
         application_command::{CommandDataOption, CommandOptionValue},
     },
     gateway::payload::incoming::InteractionCreate,
 };
 
-use crate::{Error, Metadata, context};
+use crate::{Error, Metadata, context, context::autocomplete_context::FocusedOption};
 
 pub fn parse<T: Clone + Send + Sync>(
     event: &InteractionCreate,
@@ -14,6 +14,26 @@ pub fn parse<T: Clone + Send + Sync>(
     ctx: context::Context<T>,
 ) -> Result<context::InteractionContext<T>, Error> {
     match &event.data {
+        // autocomplete interactions share their data type with application commands
+        Some(InteractionData::ApplicationCommand(command))
+            if event.kind == InteractionType::ApplicationCommandAutocomplete =>
+        {
+            let (name, options) = extract_command(&command.name, &command.options, &mut Vec::new());
+            let focused = extract_focused(options)
+                .ok_or_else(|| format!("no focused option for autocomplete on /{}", name))?;
+
+            Ok(context::InteractionContext::<T>::Autocomplete(
+                context::AutocompleteContext::from_context(
+                    meta,
+                    ctx,
+                    event.clone(),
+                    *command.clone(),
+                    name,
+                    focused,
+                    options,
+                ),
+            ))
+        }
         Some(InteractionData::ApplicationCommand(command)) => {
             let (name, options) = extract_command(&command.name, &command.options, &mut Vec::new());
             Ok(context::InteractionContext::<T>::Command(
@@ -62,3 +82,14 @@ fn extract_command<'a>(
         (parents.join(" "), options)
     }
 }
+
+fn extract_focused(options: &[CommandDataOption]) -> Option<FocusedOption> {
+    options.iter().find_map(|opt| match opt.value {
+        CommandOptionValue::Focused(ref value, kind) => Some(FocusedOption {
+            name: opt.name.clone(),
+            value: value.clone(),
+            kind,
+        }),
+        _ => None,
+    })
+}
@@ -0,0 +1,313 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::task::JoinHandle;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use uuid::Uuid;
+
+use crate::{
+    Context, Error, Registry,
+    context::JobContext,
+    middleware::{BoxFuture, HandlerInfo, HandlerKind, Middlewares},
+};
+
+/// how often the store is checked for jobs that are due
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// maximum amount of jobs taken from the store at once
+const BATCH_SIZE: usize = 32;
+/// when to try jobs again that this handler doesn't know, during deploys a
+/// newer handler might
+const UNKNOWN_JOB_DELAY: Duration = Duration::from_secs(30);
+/// how long a job can run before it's considered lost, e.g. because its
+/// handler crashed, and is run again
+const JOB_LEASE: Duration = Duration::from_secs(15 * 60);
+/// failed jobs are retried after this, doubling on every attempt
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+/// jobs that failed this often are given up on
+const MAX_ATTEMPTS: u32 = 5;
+/// when to try jobs again that middleware didn't let run, e.g. because a
+/// dependency is unavailable
+const SKIPPED_JOB_DELAY: Duration = Duration::from_secs(30);
+
+/// a one-off job, handled by the job handler registered under `name`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub name: String,
+    pub at: DateTime<Utc>,
+    pub payload: serde_json::Value,
+    /// how often the job failed
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+impl Job {
+    pub fn new(
+        name: impl Into<String>,
+        at: DateTime<Utc>,
+        payload: &impl Serialize,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: Uuid::now_v7(),
+            name: name.into(),
+            at,
+            payload: serde_json::to_value(payload)?,
+            attempts: 0,
+        })
+    }
+}
+
+/// persistent storage for jobs, so they survive restarts
+pub trait JobStore: Send + Sync {
+    fn push<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// return up to `limit` jobs due at `now`, and lease them until
+    /// `lease_until`, a leased job must never be returned twice, even when
+    /// multiple handlers share the store, jobs that weren't acknowledged or
+    /// retried when their lease runs out are due again
+    fn take_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<Job>, Error>>;
+
+    /// the job is done, remove it for good
+    fn ack<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// end the lease of a taken job, and run it again at `job.at`
+    fn retry<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+/// handle for scheduling jobs, cheap to clone
+#[derive(Clone)]
+pub struct Jobs {
+    store: Arc<dyn JobStore>,
+}
+
+impl Jobs {
+    pub fn new(store: impl JobStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+
+    /// run the job handler `name` with `payload` at `at`, or as soon as possible
+    /// if `at` is in the past, returns the id of the job
+    pub async fn schedule_once(
+        &self,
+        name: &str,
+        at: DateTime<Utc>,
+        payload: &impl Serialize,
+    ) -> Result<Uuid, Error> {
+        let job = Job::new(name, at, payload)?;
+        self.store.push(&job).await?;
+
+        Ok(job.id)
+    }
+
+    /// run the job handler `name` with `payload` after `delay`
+    pub async fn schedule_in(
+        &self,
+        name: &str,
+        delay: Duration,
+        payload: &impl Serialize,
+    ) -> Result<Uuid, Error> {
+        self.schedule_once(
+            name,
+            Utc::now() + chrono::Duration::from_std(delay)?,
+            payload,
+        )
+        .await
+    }
+}
+
+/// deserialize a job payload, used by [`crate::job_func`]
+pub fn parse_payload<P: DeserializeOwned>(job: &Job) -> Result<P, Error> {
+    serde_json::from_value(job.payload.clone())
+        .map_err(|err| format!("invalid payload for job {} ({}): {}", job.name, job.id, err).into())
+}
+
+pub(crate) struct JobRunnerHandle {
+    shutdown: CancellationToken,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl JobRunnerHandle {
+    pub(crate) fn new<T: Clone + Send + Sync + 'static>(
+        jobs: Jobs,
+        registry: Arc<Registry<T>>,
+        ctx: Context<T>,
+        middleware: Arc<Middlewares<T>>,
+    ) -> Self {
+        let shutdown = CancellationToken::new();
+
+        let runner = JobRunner {
+            jobs,
+            registry,
+            ctx,
+            middleware,
+            shutdown: shutdown.child_token(),
+            tracker: TaskTracker::new(),
+        };
+        let handle = Some(tokio::spawn(async move { runner.run().await }));
+
+        Self { shutdown, handle }
+    }
+
+    pub(crate) fn shutdown(&mut self) {
+        self.shutdown.cancel();
+    }
+
+    pub(crate) async fn join(&mut self) -> Result<(), Error> {
+        Ok(self
+            .handle
+            .take()
+            .ok_or("JobRunner already shutdown")?
+            .await?)
+    }
+}
+
+struct JobRunner<T: Clone + Send + Sync> {
+    jobs: Jobs,
+    registry: Arc<Registry<T>>,
+    ctx: Context<T>,
+    middleware: Arc<Middlewares<T>>,
+
+    shutdown: CancellationToken,
+    tracker: TaskTracker,
+}
+
+impl<T: Clone + Send + Sync + 'static> JobRunner<T> {
+    async fn run(&self) {
+        loop {
+            tokio::select! {
+                () = tokio::time::sleep(POLL_INTERVAL) => {},
+                () = self.shutdown.cancelled() => break,
+            }
+
+            let now = Utc::now();
+            match self
+                .jobs
+                .store
+                .take_due(now, now + JOB_LEASE, BATCH_SIZE)
+                .await
+            {
+                Ok(jobs) => {
+                    for job in jobs {
+                        self.spawn(job);
+                    }
+                }
+                Err(err) => tracing::warn!("error fetching due jobs: {}", err),
+            }
+        }
+
+        // let the running jobs finish, so they don't have to wait for their
+        // lease to run out before they're run again
+        self.tracker.close();
+        self.tracker.wait().await;
+    }
+
+    fn spawn(&self, mut job: Job) {
+        let Some(handler) = self.registry.jobs.get(&job.name).cloned() else {
+            tracing::warn!(
+                "no handler for job {} ({}), trying again in {}s",
+                job.name,
+                job.id,
+                UNKNOWN_JOB_DELAY.as_secs()
+            );
+
+            let jobs = self.jobs.clone();
+            self.tracker.spawn(async move {
+                job.at = Utc::now() + UNKNOWN_JOB_DELAY;
+                if let Err(err) = jobs.store.retry(&job).await {
+                    tracing::error!("error putting back job {} ({}): {}", job.name, job.id, err);
+                }
+            });
+            return;
+        };
+
+        let jobs = self.jobs.clone();
+        let ctx = self.ctx.clone();
+        let middleware = Arc::clone(&self.middleware);
+
+        self.tracker.spawn(async move {
+            let info = HandlerInfo {
+                kind: HandlerKind::Job,
+                module: &handler.module,
+                name: &handler.name,
+                meta: None,
+                interaction: None,
+                event: None,
+            };
+
+            let run = handler.run(JobContext::from_context(ctx.clone(), job.clone()));
+            let res = match middleware.run(&ctx, info, run).await {
+                Ok(true) => jobs.store.ack(&job).await,
+                // it didn't run, so it isn't an attempt
+                Ok(false) => {
+                    tracing::debug!(
+                        "job {} ({}) skipped, trying again in {}s",
+                        job.name,
+                        job.id,
+                        SKIPPED_JOB_DELAY.as_secs()
+                    );
+                    job.at = Utc::now() + SKIPPED_JOB_DELAY;
+                    jobs.store.retry(&job).await
+                }
+                Err(err) => {
+                    tracing::error!("error running job {} ({}): {}", job.name, job.id, err);
+                    retry(&jobs, job.clone()).await
+                }
+            };
+            if let Err(err) = res {
+                tracing::error!("error finishing job {} ({}): {}", job.name, job.id, err);
+            }
+        });
+    }
+}
+
+/// run a failed job again later, or give up on it after [`MAX_ATTEMPTS`]
+async fn retry(jobs: &Jobs, mut job: Job) -> Result<(), Error> {
+    job.attempts += 1;
+    if job.attempts >= MAX_ATTEMPTS {
+        tracing::error!(
+            "job {} ({}) failed {} times, giving up",
+            job.name,
+            job.id,
+            job.attempts
+        );
+        return jobs.store.ack(&job).await;
+    }
+
+    let delay = RETRY_DELAY.saturating_mul(2_u32.saturating_pow(job.attempts - 1));
+    job.at = Utc::now() + delay;
+    jobs.store.retry(&job).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Payload {
+        channel_id: u64,
+    }
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let job = Job::new("delete-channel", Utc::now(), &Payload { channel_id: 1234 })
+            .expect("couldn't create job");
+
+        assert_eq!(
+            parse_payload::<Payload>(&job).expect("couldn't parse payload"),
+            Payload { channel_id: 1234 },
+            "payload should survive a roundtrip"
+        );
+        assert!(
+            parse_payload::<String>(&job).is_err(),
+            "payload of the wrong type should fail to parse"
+        );
+    }
+}
@@ -1,22 +1,41 @@
 use std::sync::Arc;
 
 use twilight_gateway::Event;
-use twilight_model::gateway::payload::incoming::InteractionCreate;
+use twilight_model::{
+    application::interaction::InteractionType,
+    channel::message::MessageFlags,
+    gateway::payload::incoming::InteractionCreate,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
 
 pub use context::{Context, EventContext, InteractionContext};
+pub use error::CommandError;
 pub use framework::Framework;
 pub use metadata::Metadata;
+pub use middleware::{HandlerInfo, HandlerKind, Middleware, Middlewares};
 pub use module::{Module, builder::ModuleBuilder, registry::Registry};
 
 pub mod color;
+pub mod command_hash;
 pub mod context;
+pub mod cooldown;
+pub mod custom_id;
+pub mod dispatch;
+pub mod error;
 pub mod framework;
 pub mod handler;
+pub mod i18n;
 pub mod interaction;
+pub mod job;
 pub mod macros;
 pub mod metadata;
+pub mod middleware;
 pub mod module;
 pub mod scheduler;
+pub mod settings;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 
@@ -25,19 +44,75 @@ pub async fn handle_interaction<T: Clone + Send + Sync + 'static>(
     context: Context<T>,
     meta: &Metadata,
     registry: &Registry<T>,
+    middleware: &Middlewares<T>,
 ) -> Result<(), Error> {
-    match interaction::parse(&event, meta.clone(), context) {
-        Ok(InteractionContext::Command(ctx)) => {
+    match interaction::parse(&event, meta.clone(), context.clone()) {
+        Ok(InteractionContext::Autocomplete(mut ctx)) => {
+            let Some(autocomplete) = registry.find_autocomplete(&ctx.name, &ctx.focused.name)
+            else {
+                return Err(format!(
+                    "no autocomplete handler for option {} on /{}",
+                    ctx.focused.name, ctx.name
+                )
+                .into());
+            };
+            ensure_enabled(registry, &context, &autocomplete.module, &event).await?;
+            ctx.catalogue = registry.catalogue(&autocomplete.module);
+
+            if let Err(err) = middleware
+                .run(
+                    &context,
+                    interaction_info(
+                        HandlerKind::Autocomplete,
+                        &autocomplete.module,
+                        &autocomplete.command,
+                        meta,
+                        &event,
+                    ),
+                    autocomplete.run(ctx.clone()),
+                )
+                .await
+            {
+                return Err(format!(
+                    "error handling autocomplete for option {} on /{}: {}",
+                    ctx.focused.name, ctx.name, err
+                )
+                .into());
+            }
+        }
+        Ok(InteractionContext::Command(mut ctx)) => {
             let Some(command) = registry.find_command(&ctx.name) else {
                 return Err(format!("unknown command /{}", ctx.name).into());
             };
+            ensure_enabled(registry, &context, &command.module, &event).await?;
+            ctx.catalogue = registry.catalogue(&command.module);
 
-            if let Err(err) = command.run(ctx.clone()).await {
-                return Err(format!("error running command /{}: {}", ctx.name, err).into());
+            if let Err(err) = middleware
+                .run(
+                    &context,
+                    interaction_info(
+                        HandlerKind::Command,
+                        &command.module,
+                        &command.name,
+                        meta,
+                        &event,
+                    ),
+                    command.run(ctx.clone()),
+                )
+                .await
+            {
+                // the error gets reported to the user, so we don't bubble it up
+                if let Err(err) = command
+                    .report_error(&ctx, err, &context.error_message())
+                    .await
+                {
+                    return Err(format!("error running command /{}: {}", ctx.name, err).into());
+                }
             }
         }
-        Ok(InteractionContext::ComponentInteraction(ctx)) => {
-            let Some(component_interaction) = registry.components.get(&ctx.interaction.custom_id)
+        Ok(InteractionContext::ComponentInteraction(mut ctx)) => {
+            let Some((component_interaction, params)) =
+                registry.find_component(&ctx.interaction.custom_id)
             else {
                 return Err(format!(
                     "no handler for component interaction {}",
@@ -45,8 +120,24 @@ pub async fn handle_interaction<T: Clone + Send + Sync + 'static>(
                 )
                 .into());
             };
+            ensure_enabled(registry, &context, &component_interaction.module, &event).await?;
+            ctx.params = params;
+            ctx.catalogue = registry.catalogue(&component_interaction.module);
 
-            if let Err(err) = component_interaction.run(ctx.clone()).await {
+            if let Err(err) = middleware
+                .run(
+                    &context,
+                    interaction_info(
+                        HandlerKind::Component,
+                        &component_interaction.module,
+                        component_interaction.custom_id.as_str(),
+                        meta,
+                        &event,
+                    ),
+                    component_interaction.run(ctx.clone()),
+                )
+                .await
+            {
                 return Err(format!(
                     "error handling component interaction {}: {}",
                     ctx.interaction.custom_id, err
@@ -54,8 +145,29 @@ pub async fn handle_interaction<T: Clone + Send + Sync + 'static>(
                 .into());
             }
         }
-        Ok(InteractionContext::Modal(_modal_context)) => {
-            todo!()
+        Ok(InteractionContext::Modal(mut ctx)) => {
+            let Some(modal) = registry.modals.get(&ctx.data.custom_id) else {
+                return Err(format!("no handler for modal {}", ctx.data.custom_id).into());
+            };
+            ensure_enabled(registry, &context, &modal.module, &event).await?;
+            ctx.catalogue = registry.catalogue(&modal.module);
+
+            if let Err(err) = middleware
+                .run(
+                    &context,
+                    interaction_info(
+                        HandlerKind::Modal,
+                        &modal.module,
+                        &modal.custom_id,
+                        meta,
+                        &event,
+                    ),
+                    modal.run(ctx.clone()),
+                )
+                .await
+            {
+                return Err(format!("error handling modal {}: {}", ctx.data.custom_id, err).into());
+            }
         }
         Err(err) => return Err(format!("error handling interaction: {}", err).into()),
     };
@@ -63,14 +175,84 @@ pub async fn handle_interaction<T: Clone + Send + Sync + 'static>(
     Ok(())
 }
 
+fn interaction_info<'a>(
+    kind: HandlerKind,
+    module: &'a str,
+    name: &'a str,
+    meta: &'a Metadata,
+    event: &'a InteractionCreate,
+) -> HandlerInfo<'a> {
+    HandlerInfo {
+        kind,
+        module,
+        name,
+        meta: Some(meta),
+        interaction: Some(event),
+        event: None,
+    }
+}
+
+/// errors when `module` isn't enabled in the guild of the interaction, after
+/// letting the user know, so disabled modules don't leave it hanging
+async fn ensure_enabled<T: Clone + Send + Sync + 'static>(
+    registry: &Registry<T>,
+    ctx: &Context<T>,
+    module: &str,
+    event: &InteractionCreate,
+) -> Result<(), Error> {
+    if registry.module_enabled(ctx, module, event.guild_id).await? {
+        return Ok(());
+    }
+
+    let response = if event.kind == InteractionType::ApplicationCommandAutocomplete {
+        InteractionResponse {
+            kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+            data: Some(InteractionResponseDataBuilder::new().choices([]).build()),
+        }
+    } else {
+        InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(
+                InteractionResponseDataBuilder::new()
+                    .content(format!(
+                        "the {} module isn't enabled in this server",
+                        module
+                    ))
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build(),
+            ),
+        }
+    };
+    if let Err(err) = ctx
+        .interaction()
+        .create_response(event.id, &event.token, &response)
+        .await
+    {
+        tracing::warn!(
+            "error responding to interaction of disabled module: {}",
+            err
+        );
+    }
+
+    Err(format!(
+        "module {} not enabled in guild {}",
+        module,
+        event
+            .guild_id
+            .map_or_else(|| "N/A".to_string(), |id| id.to_string())
+    )
+    .into())
+}
+
 pub async fn handle<T: Clone + Send + Sync + 'static>(
     meta: Metadata,
     ctx: Context<T>,
     registry: &Registry<T>,
+    middleware: &Middlewares<T>,
     event: Event,
 ) {
     if let twilight_gateway::Event::InteractionCreate(event) = event.clone()
-        && let Err(err) = handle_interaction(*event, ctx.clone(), &meta, registry).await
+        && let Err(err) = handle_interaction(*event, ctx.clone(), &meta, registry, middleware).await
     {
         tracing::warn!(err);
     }
@@ -79,6 +261,30 @@ pub async fn handle<T: Clone + Send + Sync + 'static>(
         tracing::info!("running event handlers for {:?}", event.kind());
 
         for handler in handlers {
+            match registry
+                .module_enabled(&ctx, &handler.module, event.guild_id())
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::trace!(
+                        "skipping event handler {}, module {} not enabled",
+                        handler.uuid,
+                        handler.module
+                    );
+                    continue;
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "error checking if module {} is enabled, skipping event handler {}: {}",
+                        handler.module,
+                        handler.uuid,
+                        err
+                    );
+                    continue;
+                }
+            }
+
             let event_ctx = EventContext {
                 meta: meta.clone(),
                 application_id: ctx.application_id,
@@ -88,7 +294,16 @@ pub async fn handle<T: Clone + Send + Sync + 'static>(
                 event: event.clone(),
             };
 
-            if let Err(err) = handler.run(event_ctx).await {
+            let info = HandlerInfo {
+                kind: HandlerKind::Event,
+                module: &handler.module,
+                name: &handler.uuid,
+                meta: Some(&meta),
+                interaction: None,
+                event: Some(&event),
+            };
+
+            if let Err(err) = middleware.run(&ctx, info, handler.run(event_ctx)).await {
                 tracing::warn!("error running event handler {}: {}", handler.uuid, err);
             }
         }
@@ -4,3 +4,17 @@ macro_rules! handler_func {
         |ctx| Box::pin($func(ctx))
     };
 }
+
+/// like [`handler_func`], but for job handlers taking a typed payload as second
+/// argument, e.g. `async fn handler(ctx: JobContext, payload: Payload)`
+#[macro_export]
+macro_rules! job_func {
+    ($func:expr $(,)?) => {
+        |ctx| {
+            Box::pin(async move {
+                let payload = $crate::job::parse_payload(&ctx.job)?;
+                $func(ctx, payload).await
+            })
+        }
+    };
+}
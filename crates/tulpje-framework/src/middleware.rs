@@ -0,0 +1,132 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use twilight_gateway::Event;
+use twilight_model::{
+    gateway::payload::incoming::InteractionCreate,
+    id::{Id, marker::GuildMarker},
+};
+
+use crate::{Context, Error, Metadata};
+
+pub type BoxFuture<'a, O> = Pin<Box<dyn Future<Output = O> + Send + 'a>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandlerKind {
+    Command,
+    Autocomplete,
+    Component,
+    Modal,
+    Event,
+    Task,
+    Job,
+}
+
+impl HandlerKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Autocomplete => "autocomplete",
+            Self::Component => "component",
+            Self::Modal => "modal",
+            Self::Event => "event",
+            Self::Task => "task",
+            Self::Job => "job",
+        }
+    }
+}
+
+/// information about the handler that's about to run, passed to [`Middleware`]
+#[derive(Debug, Clone, Copy)]
+pub struct HandlerInfo<'a> {
+    pub kind: HandlerKind,
+    pub module: &'a str,
+    /// command name, custom_id, event handler uuid, task or job name
+    pub name: &'a str,
+
+    /// `None` for tasks and jobs
+    pub meta: Option<&'a Metadata>,
+    /// set for commands, autocompletes, components and modals
+    pub interaction: Option<&'a InteractionCreate>,
+    /// set for event handlers
+    pub event: Option<&'a Event>,
+}
+
+impl HandlerInfo<'_> {
+    pub fn guild_id(&self) -> Option<Id<GuildMarker>> {
+        self.interaction
+            .and_then(|interaction| interaction.guild_id)
+            .or_else(|| self.event.and_then(Event::guild_id))
+    }
+}
+
+/// hooks that run around every command, autocomplete, component, modal, event,
+/// task and job handler, in the order they were registered on the `FrameworkBuilder`
+pub trait Middleware<T: Clone + Send + Sync>: Send + Sync {
+    /// runs before the handler, returning `Ok(false)` skips the handler, any
+    /// middleware registered after this one and all `after` hooks
+    fn before<'a>(
+        &'a self,
+        _ctx: &'a Context<T>,
+        _info: &'a HandlerInfo<'a>,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async { Ok(true) })
+    }
+
+    /// runs after the handler finished, in reverse registration order
+    fn after<'a>(
+        &'a self,
+        _ctx: &'a Context<T>,
+        _info: &'a HandlerInfo<'a>,
+        _result: &'a Result<(), Error>,
+        _elapsed: Duration,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async {})
+    }
+}
+
+pub struct Middlewares<T: Clone + Send + Sync> {
+    layers: Vec<Arc<dyn Middleware<T>>>,
+}
+
+impl<T: Clone + Send + Sync> Middlewares<T> {
+    pub fn new(layers: Vec<Arc<dyn Middleware<T>>>) -> Self {
+        Self { layers }
+    }
+
+    /// run `handler` wrapped in all middleware, returns whether the handler
+    /// ran, `Ok(false)` if any middleware short-circuited
+    pub async fn run(
+        &self,
+        ctx: &Context<T>,
+        info: HandlerInfo<'_>,
+        handler: impl Future<Output = Result<(), Error>>,
+    ) -> Result<bool, Error> {
+        for layer in &self.layers {
+            if !layer.before(ctx, &info).await? {
+                tracing::debug!("middleware skipped {:?} handler {}", info.kind, info.name);
+                return Ok(false);
+            }
+        }
+
+        let start = Instant::now();
+        let result = handler.await;
+        let elapsed = start.elapsed();
+
+        for layer in self.layers.iter().rev() {
+            layer.after(ctx, &info, &result, elapsed).await;
+        }
+
+        result.map(|()| true)
+    }
+}
+
+impl<T: Clone + Send + Sync> Default for Middlewares<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
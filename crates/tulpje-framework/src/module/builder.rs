@@ -1,27 +1,47 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use async_cron_scheduler::cron::Schedule;
-use twilight_gateway::EventType;
+use twilight_gateway::{EventType, Intents};
 use twilight_model::application::command::Command;
 
 use super::{Module, command_builder::CommandBuilder};
+use crate::custom_id::CustomIdPattern;
 use crate::handler::{
+    autocomplete_handler::{AutocompleteFunc, AutocompleteHandler},
     command_handler::CommandHandler,
-    component_interaction_handler::{ComponentInteractionFunc, ComponentInteractionHandler},
+    component_interaction_handler::{
+        ComponentInteractionFunc, ComponentInteractionHandler, insert_component,
+    },
     event_handler::{EventFunc, EventHandler},
-    task_handler::{TaskFunc, TaskHandler},
+    job_handler::{JobFunc, JobHandler},
+    modal_handler::{ModalFunc, ModalHandler},
+    task_handler::{TaskFunc, TaskHandler, TaskOverlap},
 };
+use crate::i18n::Catalogue;
+use crate::settings::{Schema, Settings};
 
 pub struct ModuleBuilder<T: Clone + Send + Sync> {
     name: String,
     guild_scoped: bool,
+    owner_only: bool,
 
     commands: HashMap<String, CommandHandler<T>>,
     command_definitions: Vec<Command>,
+    owner_command_definitions: Vec<Command>,
+    autocompletes: HashMap<(String, String), AutocompleteHandler<T>>,
 
-    components: HashMap<String, ComponentInteractionHandler<T>>,
+    components: Vec<ComponentInteractionHandler<T>>,
+    modals: HashMap<String, ModalHandler<T>>,
     events: HashMap<EventType, HashSet<EventHandler<T>>>,
     tasks: HashMap<String, TaskHandler<T>>,
+    jobs: HashMap<String, JobHandler<T>>,
+    intents: Intents,
+    settings_schema: Option<Schema>,
+
+    catalogue: Catalogue,
 }
 
 impl<T: Clone + Send + Sync> ModuleBuilder<T> {
@@ -29,40 +49,109 @@ impl<T: Clone + Send + Sync> ModuleBuilder<T> {
         Self {
             name: name.into(),
             guild_scoped: false,
+            owner_only: false,
 
             commands: HashMap::new(),
             command_definitions: Vec::new(),
+            owner_command_definitions: Vec::new(),
+            autocompletes: HashMap::new(),
 
-            components: HashMap::new(),
+            components: Vec::new(),
+            modals: HashMap::new(),
             events: HashMap::new(),
             tasks: HashMap::new(),
+            jobs: HashMap::new(),
+            intents: Intents::empty(),
+            settings_schema: None,
+
+            catalogue: Catalogue::new(),
         }
     }
 
     #[must_use]
-    pub fn build(self) -> Module<T> {
+    pub fn build(mut self) -> Module<T> {
+        if self.owner_only {
+            self.owner_command_definitions
+                .append(&mut self.command_definitions);
+            for command in self.commands.values_mut() {
+                command.owner_only = true;
+            }
+        }
+
+        for command in self
+            .command_definitions
+            .iter_mut()
+            .chain(&mut self.owner_command_definitions)
+        {
+            self.catalogue.localize_command(command);
+        }
+
         Module {
             name: self.name,
             guild_scoped: self.guild_scoped,
 
             commands: self.commands,
             command_definitions: self.command_definitions,
+            owner_command_definitions: self.owner_command_definitions,
+            autocompletes: self.autocompletes,
 
             components: self.components,
+            modals: self.modals,
             events: self.events,
             tasks: self.tasks,
+            jobs: self.jobs,
+            intents: self.intents,
+            settings_schema: self.settings_schema,
+
+            catalogue: Arc::new(self.catalogue),
         }
     }
 
+    /// load the module's messages for `locale`, in the format described on
+    /// [`Catalogue`], command localizations are filled in from them on build
+    #[must_use]
+    pub fn locale(mut self, locale: &str, source: &str) -> Self {
+        self.catalogue
+            .add_locale(locale, source)
+            .unwrap_or_else(|err| panic!("failed to parse {} messages: {}", locale, err));
+        self
+    }
+
     #[must_use]
     pub fn guild(mut self) -> Self {
         self.guild_scoped = true;
         self
     }
 
+    /// make every command of the module owner only, see [`CommandBuilder::owner_only`]
+    #[must_use]
+    pub fn owner_only(mut self) -> Self {
+        self.owner_only = true;
+        self
+    }
+
+    /// gateway intents the module needs to receive its events, see
+    /// [`crate::Registry::intents`]
+    #[must_use]
+    pub fn intents(mut self, intents: Intents) -> Self {
+        self.intents |= intents;
+        self
+    }
+
+    /// per guild settings of the module, changeable through `/settings`
+    #[must_use]
+    pub fn settings<S: Settings>(mut self) -> Self {
+        self.settings_schema = Some(Schema::of::<S>());
+        self
+    }
+
     #[must_use]
     pub fn command(mut self, command: CommandBuilder<T>) -> Self {
-        self.command_definitions.push(command.clone().into());
+        if command.owner_only {
+            self.owner_command_definitions.push(command.clone().into());
+        } else {
+            self.command_definitions.push(command.clone().into());
+        }
 
         for group in &command.groups {
             for subcommand in &group.commands {
@@ -71,12 +160,16 @@ impl<T: Clone + Send + Sync> ModuleBuilder<T> {
                     .func
                     .unwrap_or_else(|| panic!("command '/{}' has no handler", command_name));
 
+                self.register_autocompletes(&command_name, &subcommand.autocompletes);
                 self.commands.insert(
                     command_name.clone(),
                     CommandHandler {
                         module: self.name.clone(),
                         name: command_name,
                         func,
+                        cooldown: subcommand.cooldown.or(command.cooldown),
+                        bot_permissions: subcommand.bot_permissions | command.bot_permissions,
+                        owner_only: command.owner_only,
                     },
                 );
             }
@@ -87,12 +180,16 @@ impl<T: Clone + Send + Sync> ModuleBuilder<T> {
                 .func
                 .unwrap_or_else(|| panic!("command /{} has no handler", command_name));
 
+            self.register_autocompletes(&command_name, &subcommand.autocompletes);
             self.commands.insert(
                 command_name.clone(),
                 CommandHandler {
                     module: self.name.clone(),
                     name: command_name,
                     func,
+                    cooldown: subcommand.cooldown.or(command.cooldown),
+                    bot_permissions: subcommand.bot_permissions | command.bot_permissions,
+                    owner_only: command.owner_only,
                 },
             );
         }
@@ -108,6 +205,9 @@ impl<T: Clone + Send + Sync> ModuleBuilder<T> {
                     module: self.name.clone(),
                     name: command.name,
                     func,
+                    cooldown: command.cooldown,
+                    bot_permissions: command.bot_permissions,
+                    owner_only: command.owner_only,
                 },
             );
         }
@@ -115,11 +215,47 @@ impl<T: Clone + Send + Sync> ModuleBuilder<T> {
         self
     }
 
+    fn register_autocompletes(
+        &mut self,
+        command_name: &str,
+        autocompletes: &HashMap<String, AutocompleteFunc<T>>,
+    ) {
+        for (option, func) in autocompletes {
+            self.autocompletes.insert(
+                (command_name.to_string(), option.clone()),
+                AutocompleteHandler {
+                    module: self.name.clone(),
+                    command: command_name.to_string(),
+                    option: option.clone(),
+                    func: *func,
+                },
+            );
+        }
+    }
+
+    /// register a component interaction handler, `custom_id` can either be an
+    /// exact id or a pattern like `emoji_stats:page:{page}`, see [`CustomIdPattern`]
+    ///
+    /// when several patterns match an id the one registered first is used, see
+    /// [`crate::Registry::find_component`]
     #[must_use]
     pub fn component(mut self, custom_id: &str, func: ComponentInteractionFunc<T>) -> Self {
-        self.components.insert(
-            custom_id.to_string(),
+        insert_component(
+            &mut self.components,
             ComponentInteractionHandler {
+                module: self.name.clone(),
+                custom_id: CustomIdPattern::new(custom_id),
+                func,
+            },
+        );
+        self
+    }
+
+    #[must_use]
+    pub fn modal(mut self, custom_id: &str, func: ModalFunc<T>) -> Self {
+        self.modals.insert(
+            custom_id.to_string(),
+            ModalHandler {
                 module: self.name.clone(),
                 custom_id: custom_id.to_string(),
                 func,
@@ -139,14 +275,42 @@ impl<T: Clone + Send + Sync> ModuleBuilder<T> {
         self
     }
 
+    /// register a task, runs are skipped while a previous run is still going
     #[must_use]
-    pub fn task(mut self, name: &str, schedule: &str, func: TaskFunc<T>) -> Self {
+    pub fn task(self, name: &str, schedule: &str, func: TaskFunc<T>) -> Self {
+        self.task_with_overlap(name, schedule, TaskOverlap::default(), func)
+    }
+
+    #[must_use]
+    pub fn task_with_overlap(
+        mut self,
+        name: &str,
+        schedule: &str,
+        overlap: TaskOverlap,
+        func: TaskFunc<T>,
+    ) -> Self {
         self.tasks.insert(
             name.to_string(),
-            TaskHandler {
+            TaskHandler::new(
+                self.name.clone(),
+                name.to_string(),
+                Schedule::try_from(schedule).expect("failed to parse cron expression"),
+                overlap,
+                func,
+            ),
+        );
+        self
+    }
+
+    /// register a handler for one-off jobs scheduled with [`crate::job::Jobs`],
+    /// use [`crate::job_func`] for handlers that take a typed payload
+    #[must_use]
+    pub fn job(mut self, name: &str, func: JobFunc<T>) -> Self {
+        self.jobs.insert(
+            name.to_string(),
+            JobHandler {
                 module: self.name.clone(),
                 name: name.to_string(),
-                cron: Schedule::try_from(schedule).expect("failed to parse cron expression"),
                 func,
             },
         );
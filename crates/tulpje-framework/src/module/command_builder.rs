@@ -13,7 +13,10 @@ use twilight_model::{
     oauth::ApplicationIntegrationType,
 };
 
-use crate::handler::command_handler::CommandFunc;
+use crate::{
+    cooldown::Cooldown,
+    handler::{autocomplete_handler::AutocompleteFunc, command_handler::CommandFunc},
+};
 
 #[derive(Debug, Clone)]
 pub struct CommandBuilder<T: Clone + Send + Sync> {
@@ -31,6 +34,9 @@ pub struct CommandBuilder<T: Clone + Send + Sync> {
     pub nsfw: Option<bool>,
 
     pub func: Option<CommandFunc<T>>,
+    pub cooldown: Option<Cooldown>,
+    pub bot_permissions: Permissions,
+    pub owner_only: bool,
     pub groups: Vec<SubCommandGroupBuilder<T>>,
     pub subcommands: Vec<SubCommandBuilder<T>>,
     pub options: Vec<CommandOption>,
@@ -53,6 +59,9 @@ impl<T: Clone + Send + Sync> CommandBuilder<T> {
             nsfw: None,
 
             func: None,
+            cooldown: None,
+            bot_permissions: Permissions::empty(),
+            owner_only: false,
             groups: Vec::new(),
             subcommands: Vec::new(),
             options: Vec::new(),
@@ -65,6 +74,29 @@ impl<T: Clone + Send + Sync> CommandBuilder<T> {
         self
     }
 
+    /// cooldown for the command, subcommands without their own cooldown inherit it
+    #[must_use]
+    pub fn cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
+    /// permissions the bot needs in the channel to run the command, checked
+    /// before the handler runs, subcommands need these on top of their own
+    #[must_use]
+    pub fn bot_permissions(mut self, permissions: Permissions) -> Self {
+        self.bot_permissions = permissions;
+        self
+    }
+
+    /// only bot owners can use the command, and it's only registered in the
+    /// dev guilds instead of globally
+    #[must_use]
+    pub fn owner_only(mut self) -> Self {
+        self.owner_only = true;
+        self
+    }
+
     #[must_use]
     pub fn group(mut self, group: SubCommandGroupBuilder<T>) -> Self {
         self.groups.push(group);
@@ -106,7 +138,7 @@ impl<T: Clone + Send + Sync> CommandBuilder<T> {
         mut self,
         localizations: impl IntoIterator<Item = (K, V)>,
     ) -> Self {
-        self.name_localizations = Some(
+        self.description_localizations = Some(
             localizations
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
@@ -120,7 +152,7 @@ impl<T: Clone + Send + Sync> CommandBuilder<T> {
         mut self,
         localizations: impl IntoIterator<Item = (K, V)>,
     ) -> Self {
-        self.description_localizations = Some(
+        self.name_localizations = Some(
             localizations
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
@@ -213,7 +245,7 @@ impl<T: Clone + Send + Sync> SubCommandGroupBuilder<T> {
         mut self,
         localizations: impl IntoIterator<Item = (K, V)>,
     ) -> Self {
-        self.name_localizations = Some(
+        self.description_localizations = Some(
             localizations
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
@@ -227,7 +259,7 @@ impl<T: Clone + Send + Sync> SubCommandGroupBuilder<T> {
         mut self,
         localizations: impl IntoIterator<Item = (K, V)>,
     ) -> Self {
-        self.description_localizations = Some(
+        self.name_localizations = Some(
             localizations
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
@@ -280,7 +312,10 @@ pub struct SubCommandBuilder<T: Clone + Send + Sync> {
     pub description_localizations: Option<HashMap<String, String>>,
 
     pub func: Option<CommandFunc<T>>,
+    pub cooldown: Option<Cooldown>,
+    pub bot_permissions: Permissions,
     pub options: Vec<CommandOption>,
+    pub autocompletes: HashMap<String, AutocompleteFunc<T>>,
 }
 
 impl<T: Clone + Send + Sync> SubCommandBuilder<T> {
@@ -293,7 +328,10 @@ impl<T: Clone + Send + Sync> SubCommandBuilder<T> {
             description_localizations: None,
 
             func: None,
+            cooldown: None,
+            bot_permissions: Permissions::empty(),
             options: Vec::new(),
+            autocompletes: HashMap::new(),
         }
     }
 
@@ -303,12 +341,25 @@ impl<T: Clone + Send + Sync> SubCommandBuilder<T> {
         self
     }
 
+    #[must_use]
+    pub fn cooldown(mut self, cooldown: Cooldown) -> Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
+    /// permissions the bot needs in the channel to run the subcommand
+    #[must_use]
+    pub fn bot_permissions(mut self, permissions: Permissions) -> Self {
+        self.bot_permissions = permissions;
+        self
+    }
+
     #[must_use]
     pub fn description_localizations<K: Into<String>, V: Into<String>>(
         mut self,
         localizations: impl IntoIterator<Item = (K, V)>,
     ) -> Self {
-        self.name_localizations = Some(
+        self.description_localizations = Some(
             localizations
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
@@ -322,7 +373,7 @@ impl<T: Clone + Send + Sync> SubCommandBuilder<T> {
         mut self,
         localizations: impl IntoIterator<Item = (K, V)>,
     ) -> Self {
-        self.description_localizations = Some(
+        self.name_localizations = Some(
             localizations
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
@@ -337,7 +388,26 @@ impl<T: Clone + Send + Sync> SubCommandBuilder<T> {
         self
     }
 
+    /// register an autocomplete handler for the option named `option`, this
+    /// also marks the option as autocompleted when building the command
+    #[must_use]
+    pub fn autocomplete(mut self, option: &str, handler: AutocompleteFunc<T>) -> Self {
+        self.autocompletes.insert(option.to_string(), handler);
+        self
+    }
+
     pub fn build(self) -> CommandOption {
+        let options = self
+            .options
+            .into_iter()
+            .map(|mut option| {
+                if self.autocompletes.contains_key(&option.name) {
+                    option.autocomplete = Some(true);
+                }
+                option
+            })
+            .collect();
+
         CommandOption {
             name: self.name,
             name_localizations: self.name_localizations,
@@ -345,7 +415,7 @@ impl<T: Clone + Send + Sync> SubCommandBuilder<T> {
             description: self.description,
             description_localizations: self.description_localizations,
 
-            options: Some(self.options),
+            options: Some(options),
             kind: CommandOptionType::SubCommand,
 
             autocomplete: None,
@@ -365,3 +435,60 @@ impl<T: Clone + Send + Sync> From<SubCommandBuilder<T>> for CommandOption {
         value.build()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn localized(value: &str) -> Option<HashMap<String, String>> {
+        Some(HashMap::from([("nl".to_string(), value.to_string())]))
+    }
+
+    #[test]
+    fn test_localizations() {
+        let command = CommandBuilder::<()>::new("stats", "show stats", CommandType::ChatInput)
+            .name_localizations([("nl", "statistieken")])
+            .description_localizations([("nl", "toon statistieken")])
+            .build();
+        assert_eq!(
+            command.name_localizations,
+            localized("statistieken"),
+            "command names should get the name localizations"
+        );
+        assert_eq!(
+            command.description_localizations,
+            localized("toon statistieken"),
+            "command descriptions should get the description localizations"
+        );
+
+        let group = SubCommandGroupBuilder::<()>::new("emoji", "emoji stats")
+            .name_localizations([("nl", "emoji")])
+            .description_localizations([("nl", "emoji statistieken")])
+            .build();
+        assert_eq!(
+            group.name_localizations,
+            localized("emoji"),
+            "group names should get the name localizations"
+        );
+        assert_eq!(
+            group.description_localizations,
+            localized("emoji statistieken"),
+            "group descriptions should get the description localizations"
+        );
+
+        let subcommand = SubCommandBuilder::<()>::new("top", "most used")
+            .name_localizations([("nl", "top")])
+            .description_localizations([("nl", "meest gebruikt")])
+            .build();
+        assert_eq!(
+            subcommand.name_localizations,
+            localized("top"),
+            "subcommand names should get the name localizations"
+        );
+        assert_eq!(
+            subcommand.description_localizations,
+            localized("meest gebruikt"),
+            "subcommand descriptions should get the description localizations"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::RwLock;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+use crate::{Context, Error};
+
+pub type GuildModulesFunc<T> =
+    fn(
+        Context<T>,
+        Id<GuildMarker>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>, Error>> + Send>>;
+
+/// how long looked up modules are cached for before fetching them again
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+type CacheEntry = (Instant, Arc<Vec<String>>);
+
+/// cached lookup of the modules enabled for a guild, used by the dispatcher to
+/// skip handlers of guild scoped modules that aren't enabled
+#[derive(Clone)]
+pub struct GuildModules<T: Clone + Send + Sync> {
+    func: Option<GuildModulesFunc<T>>,
+    ttl: Duration,
+    cache: Arc<RwLock<HashMap<Id<GuildMarker>, CacheEntry>>>,
+}
+
+impl<T: Clone + Send + Sync> GuildModules<T> {
+    pub fn new() -> Self {
+        Self {
+            func: None,
+            ttl: DEFAULT_TTL,
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub(crate) fn set_lookup(&mut self, func: GuildModulesFunc<T>, ttl: Option<Duration>) {
+        self.func = Some(func);
+        self.ttl = ttl.unwrap_or(DEFAULT_TTL);
+    }
+
+    /// returns the enabled modules for the guild, or `None` if no lookup is configured
+    pub async fn get(
+        &self,
+        ctx: &Context<T>,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<Arc<Vec<String>>>, Error> {
+        let Some(func) = self.func else {
+            return Ok(None);
+        };
+
+        if let Some((fetched_at, modules)) = self.cache.read().await.get(&guild_id)
+            && fetched_at.elapsed() < self.ttl
+        {
+            return Ok(Some(Arc::clone(modules)));
+        }
+
+        let modules = Arc::new((func)(ctx.clone(), guild_id).await?);
+        self.cache
+            .write()
+            .await
+            .insert(guild_id, (Instant::now(), Arc::clone(&modules)));
+
+        Ok(Some(modules))
+    }
+
+    /// drop the cached modules for a guild, should be called after they change
+    pub async fn invalidate(&self, guild_id: Id<GuildMarker>) {
+        self.cache.write().await.remove(&guild_id);
+    }
+
+    /// drop the cached modules for all guilds
+    pub async fn clear(&self) {
+        self.cache.write().await.clear();
+    }
+}
+
+impl<T: Clone + Send + Sync> Default for GuildModules<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,15 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use twilight_gateway::EventType;
+use twilight_gateway::{EventType, Intents};
 use twilight_model::application::command::Command;
 
 use crate::handler::{
-    command_handler::CommandHandler, component_interaction_handler::ComponentInteractionHandler,
-    event_handler::EventHandler, task_handler::TaskHandler,
+    autocomplete_handler::AutocompleteHandler, command_handler::CommandHandler,
+    component_interaction_handler::ComponentInteractionHandler, event_handler::EventHandler,
+    job_handler::JobHandler, modal_handler::ModalHandler, task_handler::TaskHandler,
 };
+use crate::i18n::Catalogue;
+use crate::settings::Schema;
 
 pub mod builder;
 pub mod command_builder;
+pub mod guild_modules;
 pub mod registry;
 
 #[derive(Clone)]
@@ -19,8 +26,18 @@ pub struct Module<T: Clone + Send + Sync> {
 
     pub(crate) commands: HashMap<String, CommandHandler<T>>,
     pub(crate) command_definitions: Vec<Command>,
+    /// only registered in dev guilds
+    pub(crate) owner_command_definitions: Vec<Command>,
+    pub(crate) autocompletes: HashMap<(String, String), AutocompleteHandler<T>>,
 
-    pub(crate) components: HashMap<String, ComponentInteractionHandler<T>>,
+    /// in registration order, which decides between overlapping patterns
+    pub(crate) components: Vec<ComponentInteractionHandler<T>>,
+    pub(crate) modals: HashMap<String, ModalHandler<T>>,
     pub(crate) events: HashMap<EventType, HashSet<EventHandler<T>>>,
     pub(crate) tasks: HashMap<String, TaskHandler<T>>,
+    pub(crate) jobs: HashMap<String, JobHandler<T>>,
+    pub(crate) intents: Intents,
+    pub(crate) settings_schema: Option<Schema>,
+
+    pub(crate) catalogue: Arc<Catalogue>,
 }
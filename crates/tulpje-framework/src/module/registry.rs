@@ -1,13 +1,29 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
-use twilight_gateway::EventType;
-use twilight_model::application::command::Command;
+use twilight_gateway::{EventType, Intents};
+use twilight_model::{
+    application::command::Command,
+    id::{Id, marker::GuildMarker},
+};
 
-use super::Module;
+use super::{
+    Module,
+    guild_modules::{GuildModules, GuildModulesFunc},
+};
 use crate::handler::{
-    command_handler::CommandHandler, component_interaction_handler::ComponentInteractionHandler,
-    event_handler::EventHandler, task_handler::TaskHandler,
+    autocomplete_handler::AutocompleteHandler,
+    command_handler::CommandHandler,
+    component_interaction_handler::{ComponentInteractionHandler, insert_component},
+    event_handler::EventHandler,
+    job_handler::JobHandler,
+    modal_handler::ModalHandler,
+    task_handler::{TaskHandler, TaskStats},
 };
+use crate::{Context, Error, i18n::Catalogue, settings::Schema};
 
 #[derive(Clone)]
 #[expect(
@@ -18,9 +34,15 @@ pub struct Registry<T: Clone + Send + Sync> {
     modules: HashMap<String, Module<T>>,
 
     pub(crate) commands: HashMap<String, CommandHandler<T>>,
-    pub(crate) components: HashMap<String, ComponentInteractionHandler<T>>,
+    pub(crate) autocompletes: HashMap<(String, String), AutocompleteHandler<T>>,
+    /// in registration order, see [`Registry::find_component`]
+    pub(crate) components: Vec<ComponentInteractionHandler<T>>,
+    pub(crate) modals: HashMap<String, ModalHandler<T>>,
     pub(crate) events: HashMap<EventType, HashSet<EventHandler<T>>>,
     pub tasks: HashMap<String, TaskHandler<T>>,
+    pub(crate) jobs: HashMap<String, JobHandler<T>>,
+
+    guild_modules: GuildModules<T>,
 }
 
 impl<T: Clone + Send + Sync> Registry<T> {
@@ -28,21 +50,71 @@ impl<T: Clone + Send + Sync> Registry<T> {
         Self {
             modules: HashMap::new(),
             commands: HashMap::new(),
-            components: HashMap::new(),
+            autocompletes: HashMap::new(),
+            components: Vec::new(),
+            modals: HashMap::new(),
             events: HashMap::new(),
             tasks: HashMap::new(),
+            jobs: HashMap::new(),
+
+            guild_modules: GuildModules::new(),
         }
     }
 
     pub fn register(&mut self, module: Module<T>) {
         self.commands.extend(module.commands.clone());
-        self.components.extend(module.components.clone());
+        self.autocompletes.extend(module.autocompletes.clone());
+        for handler in &module.components {
+            insert_component(&mut self.components, handler.clone());
+        }
+        self.modals.extend(module.modals.clone());
         self.events.extend(module.events.clone());
         self.tasks.extend(module.tasks.clone());
+        self.jobs.extend(module.jobs.clone());
 
         self.modules.insert(module.name.clone(), module);
     }
 
+    /// set the function used to look up which modules are enabled in a guild,
+    /// results are cached for `ttl` (defaults to 60 seconds)
+    pub fn guild_module_lookup(&mut self, func: GuildModulesFunc<T>, ttl: Option<Duration>) {
+        self.guild_modules.set_lookup(func, ttl);
+    }
+
+    /// drop the cached enabled modules for a guild, call this after changing them
+    pub async fn invalidate_guild_modules(&self, guild_id: Id<GuildMarker>) {
+        self.guild_modules.invalidate(guild_id).await;
+    }
+
+    /// drop the cached enabled modules for all guilds, e.g. when changes to
+    /// them might have been missed
+    pub async fn clear_guild_modules(&self) {
+        self.guild_modules.clear().await;
+    }
+
+    /// whether handlers of `module` should run for `guild_id`, modules that
+    /// aren't guild scoped and anything outside of a guild are always enabled
+    pub async fn module_enabled(
+        &self,
+        ctx: &Context<T>,
+        module: &str,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> Result<bool, Error> {
+        let Some(guild_id) = guild_id else {
+            return Ok(true);
+        };
+
+        if !self.modules.get(module).is_some_and(|m| m.guild_scoped) {
+            return Ok(true);
+        }
+
+        Ok(self
+            .guild_modules
+            .get(ctx, guild_id)
+            .await?
+            .is_none_or(|modules| modules.iter().any(|m| m == module)))
+    }
+
     pub fn global_commands(&self) -> Vec<Command> {
         self.modules
             .values()
@@ -51,6 +123,51 @@ impl<T: Clone + Send + Sync> Registry<T> {
             .collect()
     }
 
+    /// owner only commands of all modules, these should only be registered in
+    /// dev guilds
+    pub fn owner_commands(&self) -> Vec<Command> {
+        self.modules
+            .values()
+            .flat_map(|m| m.owner_command_definitions.clone())
+            .collect()
+    }
+
+    /// events the registered handlers need, interactions are included when
+    /// there are any commands, components or modals
+    pub fn event_types(&self) -> HashSet<EventType> {
+        let mut event_types: HashSet<EventType> = self.events.keys().copied().collect();
+        if !self.commands.is_empty() || !self.components.is_empty() || !self.modals.is_empty() {
+            event_types.insert(EventType::InteractionCreate);
+        }
+        event_types
+    }
+
+    /// gateway intents all registered modules need
+    pub fn intents(&self) -> Intents {
+        self.modules
+            .values()
+            .fold(Intents::empty(), |intents, m| intents | m.intents)
+    }
+
+    /// settings of all modules that declared them
+    pub fn settings_schemas(&self) -> impl Iterator<Item = &Schema> {
+        self.modules
+            .values()
+            .filter_map(|m| m.settings_schema.as_ref())
+    }
+
+    pub fn settings_schema(&self, module: &str) -> Option<&Schema> {
+        self.settings_schemas()
+            .find(|schema| schema.module == module)
+    }
+
+    /// run stats of all tasks, sorted by name
+    pub fn task_stats(&self) -> Vec<TaskStats> {
+        let mut stats: Vec<TaskStats> = self.tasks.values().map(TaskHandler::stats).collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
     pub fn module_commands(&self, module: &str) -> Option<Vec<Command>> {
         Some(self.modules.get(module)?.command_definitions.clone())
     }
@@ -59,6 +176,46 @@ impl<T: Clone + Send + Sync> Registry<T> {
         self.commands.get(name)
     }
 
+    /// find the handler for `custom_id`, exact ids take precedence over patterns,
+    /// and between patterns the first registered one that matches is used,
+    /// returns the handler and any parameters captured by its pattern
+    pub fn find_component(
+        &self,
+        custom_id: &str,
+    ) -> Option<(&ComponentInteractionHandler<T>, HashMap<String, String>)> {
+        if let Some(handler) = self
+            .components
+            .iter()
+            .find(|handler| handler.custom_id.is_exact() && handler.custom_id.as_str() == custom_id)
+        {
+            return Some((handler, HashMap::new()));
+        }
+
+        self.components.iter().find_map(|handler| {
+            handler
+                .custom_id
+                .captures(custom_id)
+                .map(|params| (handler, params))
+        })
+    }
+
+    pub fn find_autocomplete(
+        &self,
+        command: &str,
+        option: &str,
+    ) -> Option<&AutocompleteHandler<T>> {
+        self.autocompletes
+            .get(&(command.to_string(), option.to_string()))
+    }
+
+    /// messages of `module`, empty if it has none
+    pub fn catalogue(&self, module: &str) -> Arc<Catalogue> {
+        self.modules
+            .get(module)
+            .map(|module| Arc::clone(&module.catalogue))
+            .unwrap_or_default()
+    }
+
     pub fn guild_module_names(&self) -> Vec<String> {
         self.modules
             .values()
@@ -73,3 +230,66 @@ impl<T: Clone + Send + Sync> Default for Registry<T> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModuleBuilder;
+
+    fn registry() -> Registry<()> {
+        let mut registry = Registry::new();
+        registry.register(
+            ModuleBuilder::new("test")
+                .component("stats:{page}", |_| Box::pin(async { Ok(()) }))
+                .component("{kind}:2", |_| Box::pin(async { Ok(()) }))
+                .build(),
+        );
+        registry
+    }
+
+    fn find(registry: &Registry<()>, custom_id: &str) -> Option<String> {
+        registry
+            .find_component(custom_id)
+            .map(|(handler, _)| handler.custom_id.as_str().to_string())
+    }
+
+    #[test]
+    fn test_find_component() {
+        // hash maps are seeded per instance, so fresh registries would pick
+        // different handlers if the order wasn't kept
+        for _ in 0..16 {
+            assert_eq!(
+                find(&registry(), "stats:2").as_deref(),
+                Some("stats:{page}"),
+                "the first registered pattern should be used"
+            );
+        }
+        assert_eq!(
+            find(&registry(), "emoji:2").as_deref(),
+            Some("{kind}:2"),
+            "later patterns should be used when earlier ones don't match"
+        );
+        assert_eq!(find(&registry(), "other"), None, "nothing should match");
+    }
+
+    #[test]
+    fn test_find_component_exact() {
+        let mut registry = registry();
+        registry.register(
+            ModuleBuilder::new("other")
+                .component("stats:2", |_| Box::pin(async { Ok(()) }))
+                .build(),
+        );
+
+        assert_eq!(
+            find(&registry, "stats:2").as_deref(),
+            Some("stats:2"),
+            "exact ids should take precedence over patterns"
+        );
+        assert_eq!(
+            find(&registry, "stats:3").as_deref(),
+            Some("stats:{page}"),
+            "patterns should still match other ids"
+        );
+    }
+}
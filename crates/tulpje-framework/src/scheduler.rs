@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, atomic::Ordering},
+    time::Instant,
+};
 
 use async_cron_scheduler::{Job, JobId, Scheduler as CronScheduler};
 use chrono::Utc;
@@ -8,13 +12,15 @@ use tokio_util::sync::CancellationToken;
 use crate::{
     Error,
     context::{Context, TaskContext},
-    handler::task_handler::TaskHandler,
+    handler::task_handler::{TaskHandler, TaskOverlap, TaskState},
+    middleware::{HandlerInfo, HandlerKind, Middlewares},
 };
 
 pub enum SchedulerTaskMessage<T: Clone + Send + Sync> {
     Start(Vec<TaskHandler<T>>),
     Enable(Box<TaskHandler<T>>),
     Disable(String),
+    Run(Box<TaskHandler<T>>),
 }
 
 pub struct SchedulerHandle<T: Clone + Send + Sync> {
@@ -24,11 +30,15 @@ pub struct SchedulerHandle<T: Clone + Send + Sync> {
     handle: Option<JoinHandle<()>>,
 }
 impl<T: Clone + Send + Sync + 'static> SchedulerHandle<T> {
-    pub(crate) fn new(tasks: Vec<TaskHandler<T>>, ctx: Context<T>) -> Self {
+    pub(crate) fn new(
+        tasks: Vec<TaskHandler<T>>,
+        ctx: Context<T>,
+        middleware: Arc<Middlewares<T>>,
+    ) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
         let shutdown = CancellationToken::new();
 
-        let mut scheduler = Scheduler::new(ctx, receiver, shutdown.clone());
+        let mut scheduler = Scheduler::new(ctx, middleware, receiver, shutdown.clone());
         let handle = Some(tokio::spawn(async move { scheduler.run().await }));
 
         Self {
@@ -67,6 +77,12 @@ impl<T: Clone + Send + Sync + 'static> SchedulerHandle<T> {
         Ok(self.sender.send(SchedulerTaskMessage::Disable(name))?)
     }
 
+    pub(crate) fn sender(&self) -> SchedulerSender<T> {
+        SchedulerSender {
+            sender: self.sender.clone(),
+        }
+    }
+
     pub(crate) async fn join(&mut self) -> Result<(), Error> {
         Ok(self
             .handle
@@ -77,11 +93,12 @@ impl<T: Clone + Send + Sync + 'static> SchedulerHandle<T> {
 }
 
 struct Scheduler<T: Clone + Send + Sync> {
-    job_map: HashMap<String, JobId>,
+    job_map: HashMap<String, (JobId, Arc<TaskState>)>,
     scheduler: Option<CronScheduler<Utc>>,
     handle: Option<JoinHandle<()>>,
 
     ctx: Context<T>,
+    middleware: Arc<Middlewares<T>>,
     receiver: mpsc::UnboundedReceiver<SchedulerTaskMessage<T>>,
     shutdown: CancellationToken,
 }
@@ -89,6 +106,7 @@ struct Scheduler<T: Clone + Send + Sync> {
 impl<T: Clone + Send + Sync + 'static> Scheduler<T> {
     fn new(
         ctx: Context<T>,
+        middleware: Arc<Middlewares<T>>,
         receiver: mpsc::UnboundedReceiver<SchedulerTaskMessage<T>>,
         shutdown: CancellationToken,
     ) -> Self {
@@ -96,6 +114,7 @@ impl<T: Clone + Send + Sync + 'static> Scheduler<T> {
 
         Self {
             ctx,
+            middleware,
             receiver,
             shutdown,
 
@@ -107,34 +126,38 @@ impl<T: Clone + Send + Sync + 'static> Scheduler<T> {
 
     pub async fn enable_task(&mut self, handler: TaskHandler<T>) {
         let local_ctx = self.ctx.clone();
+        let local_middleware = Arc::clone(&self.middleware);
 
         let job = Job::<Utc>::cron_schedule(handler.cron.clone());
         let job_name = handler.name.clone();
+        let state = Arc::clone(&handler.state);
         let job_id = self
             .scheduler
             .as_mut()
             .unwrap()
             .insert(job, move |_id| {
-                let job_ctx = local_ctx.clone();
-                let job_handler = handler.clone();
-
-                tokio::spawn(async move {
-                    if let Err(err) = job_handler.run(TaskContext::from_context(job_ctx)).await {
-                        tracing::error!("error running task {}: {}", job_handler.name, err);
-                    };
-                });
+                spawn_task(
+                    local_ctx.clone(),
+                    Arc::clone(&local_middleware),
+                    handler.clone(),
+                );
             })
             .await;
 
-        self.job_map.insert(job_name, job_id);
+        state.paused.store(false, Ordering::Relaxed);
+        // replace the job if the task was already enabled, instead of running it twice
+        if let Some((old_job_id, _)) = self.job_map.insert(job_name, (job_id, state)) {
+            self.scheduler.as_mut().unwrap().remove(old_job_id).await;
+        }
     }
 
     pub async fn disable_task(&mut self, name: &str) {
-        let Some(job_id) = self.job_map.remove(name) else {
+        let Some((job_id, state)) = self.job_map.remove(name) else {
             return;
         };
 
         self.scheduler.as_mut().unwrap().remove(job_id).await;
+        state.paused.store(true, Ordering::Relaxed);
     }
 
     async fn run(&mut self) {
@@ -149,6 +172,11 @@ impl<T: Clone + Send + Sync + 'static> Scheduler<T> {
                         },
                         SchedulerTaskMessage::Enable(task) => self.enable_task(*task).await,
                         SchedulerTaskMessage::Disable(name) => self.disable_task(&name).await,
+                        SchedulerTaskMessage::Run(task) => spawn_task(
+                            self.ctx.clone(),
+                            Arc::clone(&self.middleware),
+                            *task,
+                        ),
                     }
                 },
                 () = self.shutdown.cancelled() => break,
@@ -166,7 +194,7 @@ impl<T: Clone + Send + Sync + 'static> Scheduler<T> {
                 return;
             };
 
-            for (_, job) in self.job_map.drain() {
+            for (_, (job, _)) in self.job_map.drain() {
                 scheduler.remove(job).await;
             }
         }
@@ -181,3 +209,90 @@ impl<T: Clone + Send + Sync + 'static> Scheduler<T> {
         }
     }
 }
+
+/// run a task right away, on its own tokio task, taking its overlap setting into account
+fn spawn_task<T: Clone + Send + Sync + 'static>(
+    ctx: Context<T>,
+    middleware: Arc<Middlewares<T>>,
+    handler: TaskHandler<T>,
+) {
+    tokio::spawn(async move {
+        let _guard = match handler.overlap {
+            TaskOverlap::Allow => None,
+            TaskOverlap::Queue => Some(handler.state.lock.lock().await),
+            TaskOverlap::Skip => {
+                let Ok(guard) = handler.state.lock.try_lock() else {
+                    tracing::warn!("task {} is still running, skipping this run", handler.name);
+                    handler.record_skip();
+                    return;
+                };
+                Some(guard)
+            }
+        };
+
+        let info = HandlerInfo {
+            kind: HandlerKind::Task,
+            module: &handler.module,
+            name: &handler.name,
+            meta: None,
+            interaction: None,
+            event: None,
+        };
+
+        // only record runs that weren't skipped by middleware
+        let run = async {
+            handler.record_start();
+            let start = Instant::now();
+            let result = handler.run(TaskContext::from_context(ctx.clone())).await;
+            handler.record_finish(start.elapsed(), &result);
+            result
+        };
+
+        if let Err(err) = middleware.run(&ctx, info, run).await {
+            tracing::error!("error running task {}: {}", handler.name, err);
+        }
+    });
+}
+
+/// cloneable handle for enabling, disabling and running tasks from outside the framework
+pub struct SchedulerSender<T: Clone + Send + Sync> {
+    sender: mpsc::UnboundedSender<SchedulerTaskMessage<T>>,
+}
+
+impl<T: Clone + Send + Sync> Clone for SchedulerSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync> SchedulerSender<T> {
+    /// (re)schedule a task, resuming it if it was disabled
+    pub fn enable_task(
+        &self,
+        handler: TaskHandler<T>,
+    ) -> Result<(), Box<mpsc::error::SendError<SchedulerTaskMessage<T>>>> {
+        Ok(self
+            .sender
+            .send(SchedulerTaskMessage::Enable(Box::new(handler)))?)
+    }
+
+    /// stop scheduling a task, runs that already started aren't cancelled
+    pub fn disable_task(
+        &self,
+        name: String,
+    ) -> Result<(), Box<mpsc::error::SendError<SchedulerTaskMessage<T>>>> {
+        Ok(self.sender.send(SchedulerTaskMessage::Disable(name))?)
+    }
+
+    /// run a task now, outside of its schedule, it still goes through middleware
+    pub fn run_task(
+        &self,
+        handler: TaskHandler<T>,
+    ) -> Result<(), Box<mpsc::error::SendError<SchedulerTaskMessage<T>>>> {
+        Ok(self
+            .sender
+            .send(SchedulerTaskMessage::Run(Box::new(handler)))?)
+    }
+}
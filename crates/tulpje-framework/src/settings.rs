@@ -0,0 +1,172 @@
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+
+use crate::CommandError;
+
+/// settings of a module, stored per guild as a json object
+///
+/// fields should have defaults (`#[serde(default)]`), so settings stored by
+/// an older version of the module can still be read
+pub trait Settings: Serialize + DeserializeOwned + Default + Send + Sync {
+    /// name the settings are stored under, usually the module name
+    const MODULE: &'static str;
+
+    /// settings that can be changed through `/settings`, keys are field names
+    fn schema() -> Vec<Setting>;
+}
+
+/// type of a setting's value, values are entered as text and parsed according
+/// to the kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    Bool,
+    Integer { min: i64, max: i64 },
+    String { max_length: usize },
+    Channel,
+    Role,
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub kind: SettingKind,
+}
+
+impl Setting {
+    pub fn new(key: &'static str, description: &'static str, kind: SettingKind) -> Self {
+        Self {
+            key,
+            description,
+            kind,
+        }
+    }
+
+    /// parse a value entered by a user, channels and roles can be mentions or ids
+    pub fn parse(&self, input: &str) -> Result<Value, CommandError> {
+        let input = input.trim();
+
+        match self.kind {
+            SettingKind::Bool => match input.to_lowercase().as_str() {
+                "true" | "yes" | "on" | "enabled" => Ok(Value::Bool(true)),
+                "false" | "no" | "off" | "disabled" => Ok(Value::Bool(false)),
+                _ => Err(CommandError::user(format!(
+                    "`{}` should be `true` or `false`",
+                    self.key
+                ))),
+            },
+            SettingKind::Integer { min, max } => match input.parse::<i64>() {
+                Ok(value) if (min..=max).contains(&value) => Ok(Value::from(value)),
+                _ => Err(CommandError::user(format!(
+                    "`{}` should be a number from {} to {}",
+                    self.key, min, max
+                ))),
+            },
+            SettingKind::String { max_length } => {
+                if input.chars().count() > max_length {
+                    return Err(CommandError::user(format!(
+                        "`{}` can be at most {} characters",
+                        self.key, max_length
+                    )));
+                }
+
+                Ok(Value::String(input.to_string()))
+            }
+            SettingKind::Channel => parse_id(input, "<#", ">")
+                .ok_or_else(|| CommandError::user(format!("`{}` should be a channel", self.key))),
+            SettingKind::Role => parse_id(input, "<@&", ">")
+                .ok_or_else(|| CommandError::user(format!("`{}` should be a role", self.key))),
+        }
+    }
+
+    /// format a stored value for showing it to users
+    pub fn display(&self, value: &Value) -> String {
+        match (self.kind, value) {
+            (SettingKind::Channel, Value::String(id)) => format!("<#{id}>"),
+            (SettingKind::Role, Value::String(id)) => format!("<@&{id}>"),
+            (_, Value::String(value)) => format!("`{value}`"),
+            (_, Value::Null) => "not set".to_string(),
+            (_, value) => format!("`{value}`"),
+        }
+    }
+}
+
+/// ids are stored as strings, like twilight (de)serializes them
+fn parse_id(input: &str, prefix: &str, suffix: &str) -> Option<Value> {
+    let id = input
+        .strip_prefix(prefix)
+        .and_then(|id| id.strip_suffix(suffix))
+        .unwrap_or(input);
+
+    id.parse::<u64>()
+        .ok()
+        .filter(|id| *id != 0)
+        .map(|id| Value::String(id.to_string()))
+}
+
+/// the settings a module declared, used by `/settings`
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub module: &'static str,
+    pub settings: Vec<Setting>,
+    /// the values used for settings a guild hasn't set
+    pub defaults: Map<String, Value>,
+}
+
+impl Schema {
+    pub fn of<S: Settings>() -> Self {
+        let defaults = match serde_json::to_value(S::default()) {
+            Ok(Value::Object(defaults)) => defaults,
+            _ => Map::new(),
+        };
+
+        Self {
+            module: S::MODULE,
+            settings: S::schema(),
+            defaults,
+        }
+    }
+
+    pub fn setting(&self, key: &str) -> Option<&Setting> {
+        self.settings.iter().find(|setting| setting.key == key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let channel = Setting::new("channel", "", SettingKind::Channel);
+        assert_eq!(
+            channel.parse("<#1234>").ok(),
+            Some(Value::String("1234".into())),
+            "channel mentions should be parsed"
+        );
+        assert_eq!(
+            channel.parse("1234").ok(),
+            Some(Value::String("1234".into())),
+            "channel ids should be parsed"
+        );
+        assert!(
+            channel.parse("<@&1234>").is_err(),
+            "role mentions aren't channels"
+        );
+
+        let limit = Setting::new("limit", "", SettingKind::Integer { min: 1, max: 10 });
+        assert_eq!(
+            limit.parse(" 10 ").ok(),
+            Some(Value::from(10)),
+            "integers should be parsed"
+        );
+        assert!(limit.parse("11").is_err(), "integers should be in range");
+
+        let enabled = Setting::new("enabled", "", SettingKind::Bool);
+        assert_eq!(
+            enabled.parse("Off").ok(),
+            Some(Value::Bool(false)),
+            "booleans should be parsed case insensitively"
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! in-process test harness, feeds synthetic events through a [`Framework`] and
+//! captures the requests it makes to a mock discord http server
+//!
+//! only available with the `testing` feature
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::Notify,
+    task::JoinHandle,
+};
+use twilight_gateway::Event;
+use twilight_http::Client;
+use twilight_model::{
+    gateway::payload::incoming::InteractionCreate,
+    id::{Id, marker::ApplicationMarker},
+};
+
+use crate::{Error, Framework, Metadata, Registry, framework::FrameworkBuilder};
+
+pub const APPLICATION_ID: u64 = 1;
+pub const GUILD_ID: u64 = 2;
+pub const USER_ID: u64 = 3;
+
+/// a request the framework sent to discord
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    /// path without the `/api/v10` prefix and query string, e.g.
+    /// `/interactions/5/token/callback`
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+#[derive(Clone)]
+struct MockResponse {
+    status: u16,
+    body: String,
+}
+
+#[derive(Default)]
+struct MockState {
+    requests: Mutex<Vec<Request>>,
+    /// keyed by method and path
+    responses: Mutex<HashMap<(String, String), MockResponse>>,
+    received: Notify,
+}
+
+/// http server standing in for the discord api, it records every request and
+/// answers with the response set through [`MockDiscord::respond`], or an empty
+/// `200 OK` json object
+pub struct MockDiscord {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    handle: JoinHandle<()>,
+}
+
+impl MockDiscord {
+    pub async fn start() -> Result<Self, Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(MockState::default());
+
+        let server_state = Arc::clone(&state);
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = Arc::clone(&server_state);
+                tokio::spawn(async move {
+                    if let Err(err) = serve(stream, &state).await {
+                        tracing::warn!("mock discord connection error: {}", err);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            handle,
+        })
+    }
+
+    /// a client that sends its requests to this server
+    pub fn client(&self) -> Client {
+        Client::builder()
+            .proxy(self.addr.to_string(), true)
+            .ratelimiter(None)
+            .token("test".into())
+            .build()
+    }
+
+    /// answer requests to `method` `path` (without the `/api/v10` prefix) with
+    /// `status` and `body` instead of an empty object
+    pub fn respond(&self, method: &str, path: &str, status: u16, body: &serde_json::Value) {
+        self.state
+            .responses
+            .lock()
+            .expect("mock responses lock poisoned")
+            .insert(
+                (method.to_uppercase(), path.to_string()),
+                MockResponse {
+                    status,
+                    body: body.to_string(),
+                },
+            );
+    }
+
+    /// all requests received so far
+    pub fn requests(&self) -> Vec<Request> {
+        self.state
+            .requests
+            .lock()
+            .expect("mock requests lock poisoned")
+            .clone()
+    }
+
+    /// wait until at least `count` requests were received, handlers run in the
+    /// background so this is how tests know they're done
+    pub async fn wait_for_requests(
+        &self,
+        count: usize,
+        timeout: Duration,
+    ) -> Result<Vec<Request>, Error> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                // register interest before checking, so a request received in
+                // between isn't missed
+                let received = self.state.received.notified();
+                let requests = self.requests();
+                if requests.len() >= count {
+                    return requests;
+                }
+                received.await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            format!(
+                "expected {} requests, got {} within {:?}",
+                count,
+                self.requests().len(),
+                timeout
+            )
+            .into()
+        })
+    }
+}
+
+impl Drop for MockDiscord {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// minimal http/1.1, enough for the requests twilight sends
+async fn serve(stream: TcpStream, state: &MockState) -> Result<(), Error> {
+    let mut stream = BufReader::new(stream);
+
+    loop {
+        let mut request_line = String::new();
+        if stream.read_line(&mut request_line).await? == 0 {
+            return Ok(()); // connection closed
+        }
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().ok_or("missing method")?.to_string();
+        let path = parts.next().ok_or("missing path")?;
+        let path = path.split('?').next().unwrap_or(path);
+        let path = path.strip_prefix("/api/v10").unwrap_or(path).to_string();
+
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            stream.read_line(&mut header).await?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse()?;
+            }
+        }
+
+        let mut body = vec![0; content_length];
+        stream.read_exact(&mut body).await?;
+
+        let response = state
+            .responses
+            .lock()
+            .map_err(|_| "mock responses lock poisoned")?
+            .get(&(method.clone(), path.clone()))
+            .cloned()
+            .unwrap_or(MockResponse {
+                status: 200,
+                body: "{}".into(),
+            });
+
+        state
+            .requests
+            .lock()
+            .map_err(|_| "mock requests lock poisoned")?
+            .push(Request { method, path, body });
+        state.received.notify_waiters();
+
+        stream
+            .get_mut()
+            .write_all(
+                format!(
+                    "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                    response.status,
+                    response.body.len(),
+                    response.body
+                )
+                .as_bytes(),
+            )
+            .await?;
+    }
+}
+
+/// a [`Framework`] connected to a [`MockDiscord`]
+pub struct TestHarness<T: Clone + Send + Sync> {
+    pub discord: MockDiscord,
+    pub framework: Framework<T>,
+}
+
+impl<T: Clone + Send + Sync + 'static> TestHarness<T> {
+    /// build and start a framework for `registry`, `services` can be anything
+    /// the handlers under test need
+    pub async fn new(registry: Registry<T>, services: T) -> Result<Self, Error> {
+        let discord = MockDiscord::start().await?;
+        let mut framework = FrameworkBuilder::new(
+            Arc::new(registry),
+            discord.client(),
+            Id::<ApplicationMarker>::new(APPLICATION_ID),
+            services,
+        )
+        .build();
+        framework.start().await?;
+
+        Ok(Self { discord, framework })
+    }
+
+    /// queue an event, like it was received from the gateway
+    pub async fn send(&mut self, event: Event) -> Result<(), Error> {
+        self.framework
+            .send(metadata(), event, None)
+            .await
+            .map_err(|err| format!("error sending event: {}", err))?;
+
+        Ok(())
+    }
+
+    /// stop the framework, waiting for running handlers to finish
+    pub async fn shutdown(mut self) -> Result<(), Error> {
+        self.framework.shutdown().await;
+        self.framework.join().await
+    }
+}
+
+pub fn metadata() -> Metadata {
+    Metadata {
+        uuid: uuid::Uuid::now_v7(),
+        shard: 0,
+    }
+}
+
+/// a slash command invoked by [`USER_ID`] in [`GUILD_ID`], `options` is the raw
+/// option list as discord sends it, e.g. `[{"name": "module", "type": 3, "value": "emoji"}]`
+pub fn command(name: &str, options: serde_json::Value) -> Result<InteractionCreate, Error> {
+    let user = json!({
+        "id": USER_ID.to_string(),
+        "username": "test",
+        "discriminator": "0",
+        "avatar": null,
+    });
+
+    Ok(InteractionCreate(serde_json::from_value(json!({
+        "id": "5",
+        "application_id": APPLICATION_ID.to_string(),
+        "type": 2,
+        "token": "token",
+        "guild_id": GUILD_ID.to_string(),
+        "member": {
+            "user": user,
+            "roles": [],
+            "joined_at": "2024-01-01T00:00:00.000000+00:00",
+            "deaf": false,
+            "mute": false,
+            "flags": 0,
+        },
+        "authorizing_integration_owners": {},
+        "entitlements": [],
+        "data": {
+            "id": "6",
+            "name": name,
+            "type": 1,
+            "options": options,
+        },
+    }))?))
+}
+
+/// [`command`] wrapped in a gateway event
+pub fn command_event(name: &str, options: serde_json::Value) -> Result<Event, Error> {
+    Ok(Event::InteractionCreate(Box::new(command(name, options)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::application::command::CommandType;
+
+    use super::*;
+    use crate::{
+        ModuleBuilder, context::CommandContext, handler_func,
+        module::command_builder::CommandBuilder,
+    };
+
+    async fn ping(ctx: CommandContext<()>) -> Result<(), Error> {
+        ctx.reply("pong").await?;
+        Ok(())
+    }
+
+    async fn defer_then_fail(ctx: CommandContext<()>) -> Result<(), Error> {
+        ctx.defer().await?;
+        Err("something went wrong".into())
+    }
+
+    #[tokio::test]
+    async fn test_command_reply() {
+        let mut registry = Registry::new();
+        registry.register(
+            ModuleBuilder::new("test")
+                .command(
+                    CommandBuilder::new("ping", "ping", CommandType::ChatInput)
+                        .handler(handler_func!(ping)),
+                )
+                .build(),
+        );
+
+        let mut harness = TestHarness::new(registry, ())
+            .await
+            .expect("couldn't start test harness");
+        harness
+            .send(command_event("ping", json!([])).expect("couldn't create command"))
+            .await
+            .expect("couldn't send event");
+
+        let requests = harness
+            .discord
+            .wait_for_requests(1, Duration::from_secs(5))
+            .await
+            .expect("command should respond");
+        let request = requests.first().expect("command should respond");
+        assert_eq!(
+            request.path, "/interactions/5/token/callback",
+            "command should respond to the interaction"
+        );
+        assert_eq!(
+            request
+                .json()
+                .expect("response should be json")
+                .pointer("/data/content"),
+            Some(&json!("pong")),
+            "command should reply with pong"
+        );
+
+        harness
+            .shutdown()
+            .await
+            .expect("couldn't shut down test harness");
+    }
+
+    #[tokio::test]
+    async fn test_command_error_after_defer() {
+        let mut registry = Registry::new();
+        registry.register(
+            ModuleBuilder::new("test")
+                .command(
+                    CommandBuilder::new("fail", "fail", CommandType::ChatInput)
+                        .handler(handler_func!(defer_then_fail)),
+                )
+                .build(),
+        );
+
+        let mut harness = TestHarness::new(registry, ())
+            .await
+            .expect("couldn't start test harness");
+        harness
+            .send(command_event("fail", json!([])).expect("couldn't create command"))
+            .await
+            .expect("couldn't send event");
+
+        let requests = harness
+            .discord
+            .wait_for_requests(2, Duration::from_secs(5))
+            .await
+            .expect("command should defer and report the error");
+        let error = requests.get(1).expect("error should be reported");
+        assert_eq!(
+            (error.method.as_str(), error.path.as_str()),
+            ("PATCH", "/webhooks/1/token/messages/@original"),
+            "error should replace the deferred response"
+        );
+
+        harness
+            .shutdown()
+            .await
+            .expect("couldn't shut down test harness");
+    }
+}
@@ -0,0 +1,251 @@
+use std::error::Error;
+
+use futures_util::{StreamExt as _, future, stream::FuturesUnordered};
+use redis::aio::ConnectionManager as RedisConnectionManager;
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use twilight_gateway::{Intents, Shard, ShardId};
+use twilight_model::gateway::payload::outgoing::identify::IdentifyProperties;
+
+use crate::{
+    config::ShardRange,
+    event_filter::EventFilter,
+    session::{self, SavedSession},
+    shard_manager::ShardManagerHandle,
+    shard_reporter::ShardReporterHandle,
+};
+
+/// everything shared between the shards of a process
+#[derive(Clone)]
+pub(crate) struct ClusterContext {
+    pub(crate) token: String,
+    pub(crate) intents: Intents,
+    pub(crate) gateway_queue: String,
+    pub(crate) amqp_tx: UnboundedSender<Vec<u8>>,
+    pub(crate) event_filter: EventFilter,
+    pub(crate) redis: RedisConnectionManager,
+    pub(crate) process_name: String,
+}
+
+/// the shards a process runs for one sharding generation, each with their own
+/// manager and reporter
+pub(crate) struct ShardCluster {
+    pub(crate) generation: u64,
+    pub(crate) range: ShardRange,
+    managers: Vec<(ShardId, ShardManagerHandle)>,
+    manager_joins: FuturesUnordered<JoinHandle<Option<(ShardId, SavedSession)>>>,
+    reporters: Vec<ShardReporterHandle>,
+    reporter_joins: Vec<JoinHandle<()>>,
+}
+
+impl ShardCluster {
+    /// create the shards, they don't connect until [`ShardCluster::start`],
+    /// shards resume the session saved by the previous process if there is one,
+    /// shards that never start hand it back on shutdown
+    pub(crate) async fn new(
+        ctx: &ClusterContext,
+        generation: u64,
+        range: ShardRange,
+        shard_count: u32,
+        forward: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut cluster = Self {
+            generation,
+            range,
+            managers: Vec::new(),
+            manager_joins: FuturesUnordered::new(),
+            reporters: Vec::new(),
+            reporter_joins: Vec::new(),
+        };
+
+        for number in range.ids() {
+            let shard_id = ShardId::new_checked(number, shard_count)
+                .ok_or_else(|| format!("invalid shard {number} of {shard_count}"))?;
+            let saved_session = session::take(&ctx.redis, shard_id)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::warn!("error loading session of shard {}: {}", number, err);
+                    None
+                })
+                .filter(|saved_session| {
+                    // identify instead, so the shard gets the intents it needs now
+                    let same_intents = saved_session.intents == ctx.intents;
+                    if !same_intents {
+                        tracing::info!("intents of shard {} changed, not resuming", number);
+                    }
+                    same_intents
+                });
+            if saved_session.is_some() {
+                tracing::info!("resuming session of shard {}", number);
+            }
+            let resume_url = saved_session
+                .as_ref()
+                .and_then(|saved_session| saved_session.resume_url.clone());
+            let shard = Shard::with_config(shard_id, create_shard_config(ctx, saved_session));
+
+            let (reporter_join, reporter) =
+                ShardReporterHandle::new(ctx.redis.clone(), shard_id, ctx.process_name.clone());
+            let (manager_join, manager) = ShardManagerHandle::new(
+                shard,
+                resume_url,
+                ctx.amqp_tx.clone(),
+                ctx.event_filter.clone(),
+                reporter.clone(),
+                forward,
+            );
+
+            cluster.managers.push((shard_id, manager));
+            cluster.manager_joins.push(manager_join);
+            cluster.reporters.push(reporter);
+            cluster.reporter_joins.push(reporter_join);
+        }
+
+        Ok(cluster)
+    }
+
+    /// connect the shards one by one, as the gateway queue allows, stops
+    /// early when `shutdown` is cancelled
+    pub(crate) async fn start(
+        &self,
+        ctx: &ClusterContext,
+        shutdown: &CancellationToken,
+    ) -> Result<(), Box<dyn Error>> {
+        for (shard_id, manager) in &self.managers {
+            tracing::info!("waiting for gateway queue (shard {})...", shard_id.number());
+            tokio::select! {
+                res = reqwest::get(format!(
+                    "{}?shard={}",
+                    ctx.gateway_queue,
+                    shard_id.number()
+                )) => {
+                    res.map_err(|err| format!("error waiting for gateway queue: {err}"))?;
+                },
+                () = shutdown.cancelled() => return Ok(()),
+            }
+
+            manager
+                .start()
+                .map_err(|err| format!("error starting shard {}: {}", shard_id.number(), err))?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn shard_ids(&self) -> impl Iterator<Item = ShardId> + '_ {
+        self.managers.iter().map(|(shard_id, _)| *shard_id)
+    }
+
+    pub(crate) fn forward(&self, forward: bool) {
+        for (shard_id, manager) in &self.managers {
+            if let Err(err) = manager.forward(forward) {
+                tracing::warn!(
+                    "error changing forwarding of shard {}: {}",
+                    shard_id.number(),
+                    err
+                );
+            }
+        }
+    }
+
+    /// wait until every shard is ready, false if any of them stopped instead
+    pub(crate) async fn wait_ready(&mut self) -> bool {
+        future::join_all(
+            self.managers
+                .iter_mut()
+                .map(|(_, manager)| manager.wait_ready()),
+        )
+        .await
+        .iter()
+        .all(Result::is_ok)
+    }
+
+    /// wait until any of the shards stops, which they only do by themselves
+    /// when their connection can't be recovered
+    pub(crate) async fn stopped(&mut self) {
+        match self.manager_joins.next().await {
+            Some(Err(err)) => tracing::error!("error joining shard manager: {err}"),
+            Some(Ok(_)) => {}
+            None => future::pending().await,
+        }
+    }
+
+    /// disconnect, and save the sessions so the next process can resume them
+    pub(crate) async fn shutdown(mut self, redis: &RedisConnectionManager) {
+        for (_, manager) in &mut self.managers {
+            manager.shutdown();
+        }
+
+        for (shard_id, saved_session) in self.join().await {
+            if let Err(err) = session::store(redis, shard_id, &saved_session).await {
+                tracing::warn!(
+                    "error saving session of shard {}: {}",
+                    shard_id.number(),
+                    err
+                );
+            }
+        }
+    }
+
+    /// disconnect for good, for shards that were replaced by a new generation
+    pub(crate) async fn retire(mut self) {
+        // stop reporting first, so the states of the shards that replaced
+        // these don't get overwritten
+        for reporter in &mut self.reporters {
+            reporter.retire();
+        }
+        for (shard_id, manager) in &self.managers {
+            if let Err(err) = manager.retire() {
+                tracing::warn!("error retiring shard {}: {}", shard_id.number(), err);
+            }
+        }
+
+        self.join().await;
+    }
+
+    /// wait for the shards to stop, returns the sessions that can be resumed
+    async fn join(mut self) -> Vec<(ShardId, SavedSession)> {
+        let mut sessions = Vec::new();
+        while let Some(res) = self.manager_joins.next().await {
+            match res {
+                Ok(Some(saved_session)) => sessions.push(saved_session),
+                Ok(None) => {}
+                Err(err) => tracing::error!("error joining shard manager: {err}"),
+            }
+        }
+
+        for reporter in &mut self.reporters {
+            reporter.shutdown();
+        }
+        tracing::trace!("waiting for shard reporters to exit...");
+        for reporter_join in self.reporter_joins {
+            if let Err(err) = reporter_join.await {
+                tracing::error!("error joining shard reporter: {err}");
+            }
+        }
+
+        sessions
+    }
+}
+
+fn create_shard_config(
+    ctx: &ClusterContext,
+    saved_session: Option<SavedSession>,
+) -> twilight_gateway::Config {
+    let mut builder = twilight_gateway::ConfigBuilder::new(ctx.token.clone(), ctx.intents)
+        .presence(crate::create_presence())
+        .identify_properties(IdentifyProperties {
+            browser: "tulpje".into(),
+            device: "tulpje".into(),
+            os: std::env::consts::OS.into(),
+        });
+
+    // twilight identifies instead when the session turns out to be invalid
+    if let Some(saved_session) = saved_session {
+        builder = builder.session(saved_session.session);
+        if let Some(resume_url) = saved_session.resume_url {
+            builder = builder.resume_url(resume_url);
+        }
+    }
+
+    builder.build()
+}
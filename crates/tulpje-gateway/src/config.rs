@@ -1,16 +1,29 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
 use figment::{Figment, providers::Env};
 use figment_file_provider_adapter::FileAdapter;
 use serde::{Deserialize, Serialize};
 
-use tulpje_common::metrics::MetricsListenAddr;
+use tulpje_common::{metrics::MetricsListenAddr, sharding::Sharding};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     pub discord_token: String,
     pub discord_proxy: String,
     pub discord_gateway_queue: String,
-    pub shard_id: u32,
     pub shard_count: u32,
+    /// run a single shard, kept for existing deployments, see `shards`
+    #[serde(default)]
+    pub shard_id: Option<u32>,
+    /// shards to run in this process, like `0-3`
+    #[serde(default)]
+    pub shards: Option<ShardRange>,
+    /// run the shards of a cluster, clusters are consecutive ranges of
+    /// `cluster_size` shards
+    #[serde(default)]
+    pub cluster_id: Option<u32>,
+    #[serde(default)]
+    pub cluster_size: Option<u32>,
     pub rabbitmq_address: String,
     pub redis_url: String,
 
@@ -24,4 +37,181 @@ impl Config {
             .merge(FileAdapter::wrap(Env::raw()))
             .extract()?)
     }
+
+    /// use the shard count and cluster size of the last reshard, only applies
+    /// to clusters, other processes always run the shards they're told to
+    pub fn apply_sharding(&mut self, sharding: &Sharding) {
+        if self.cluster_id.is_some() {
+            self.shard_count = sharding.shard_count;
+            self.cluster_size = Some(sharding.cluster_size);
+        }
+    }
+
+    pub fn process_name(&self) -> Result<String, String> {
+        Ok(match self.cluster_id {
+            Some(cluster_id) => format!("gateway-cluster-{}", cluster_id),
+            None => format!("gateway-{}", self.shard_range()?),
+        })
+    }
+
+    /// the shards this process should run, from `shards`, the cluster or
+    /// `shard_id`, in that order
+    pub fn shard_range(&self) -> Result<ShardRange, String> {
+        let range = match (
+            self.shards,
+            self.cluster_id,
+            self.cluster_size,
+            self.shard_id,
+        ) {
+            (Some(range), ..) => range,
+            (None, Some(cluster_id), Some(cluster_size), _) => {
+                ShardRange::cluster(cluster_id, cluster_size, self.shard_count)?
+            }
+            (None, Some(_), None, _) | (None, None, Some(_), _) => {
+                return Err("CLUSTER_ID and CLUSTER_SIZE need to be set together".into());
+            }
+            (None, None, None, Some(shard_id)) => ShardRange::new(shard_id, shard_id)?,
+            (None, None, None, None) => {
+                return Err("one of SHARDS, CLUSTER_ID or SHARD_ID needs to be set".into());
+            }
+        };
+
+        if range.last >= self.shard_count {
+            return Err(format!(
+                "shards {} are out of range for a shard count of {}",
+                range, self.shard_count
+            ));
+        }
+
+        Ok(range)
+    }
+}
+
+/// an inclusive range of shard ids
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "ShardRangeValue", into = "String")]
+pub struct ShardRange {
+    pub first: u32,
+    pub last: u32,
+}
+
+impl ShardRange {
+    pub fn new(first: u32, last: u32) -> Result<Self, String> {
+        if first > last {
+            return Err(format!("invalid shard range {first}-{last}"));
+        }
+
+        Ok(Self { first, last })
+    }
+
+    pub fn cluster(cluster_id: u32, cluster_size: u32, shard_count: u32) -> Result<Self, String> {
+        if cluster_size == 0 {
+            return Err("CLUSTER_SIZE can't be 0".into());
+        }
+
+        let first = cluster_id
+            .checked_mul(cluster_size)
+            .filter(|first| *first < shard_count)
+            .ok_or_else(|| format!("cluster {cluster_id} has no shards"))?;
+        let last = first.saturating_add(cluster_size - 1).min(shard_count - 1);
+
+        Self::new(first, last)
+    }
+
+    pub fn ids(&self) -> RangeInclusive<u32> {
+        self.first..=self.last
+    }
+}
+
+impl fmt::Display for ShardRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}-{}", self.first, self.last)
+        }
+    }
+}
+
+impl FromStr for ShardRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |id: &str| {
+            id.trim()
+                .parse::<u32>()
+                .map_err(|err| format!("invalid shard id '{id}': {err}"))
+        };
+
+        match s.split_once('-') {
+            Some((first, last)) => Self::new(parse(first)?, parse(last)?),
+            None => {
+                let id = parse(s)?;
+                Self::new(id, id)
+            }
+        }
+    }
+}
+
+/// figment parses `SHARDS=3` as a number and `SHARDS=0-3` as a string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ShardRangeValue {
+    Single(u32),
+    Range(String),
+}
+
+impl TryFrom<ShardRangeValue> for ShardRange {
+    type Error = String;
+
+    fn try_from(value: ShardRangeValue) -> Result<Self, Self::Error> {
+        match value {
+            ShardRangeValue::Single(id) => Self::new(id, id),
+            ShardRangeValue::Range(range) => range.parse(),
+        }
+    }
+}
+
+impl From<ShardRange> for String {
+    fn from(range: ShardRange) -> Self {
+        range.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_range() {
+        assert_eq!(
+            "0-3".parse(),
+            Ok(ShardRange { first: 0, last: 3 }),
+            "ranges should be parsed"
+        );
+        assert_eq!(
+            "2".parse(),
+            Ok(ShardRange { first: 2, last: 2 }),
+            "single shards should be parsed"
+        );
+        assert!(
+            "3-0".parse::<ShardRange>().is_err(),
+            "backwards ranges should be rejected"
+        );
+
+        assert_eq!(
+            ShardRange::cluster(1, 4, 10),
+            Ok(ShardRange { first: 4, last: 7 }),
+            "clusters should be consecutive ranges"
+        );
+        assert_eq!(
+            ShardRange::cluster(2, 4, 10),
+            Ok(ShardRange { first: 8, last: 9 }),
+            "the last cluster should be cut off at the shard count"
+        );
+        assert!(
+            ShardRange::cluster(3, 4, 10).is_err(),
+            "clusters past the shard count should be rejected"
+        );
+    }
 }
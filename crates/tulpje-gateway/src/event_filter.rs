@@ -0,0 +1,151 @@
+use std::{collections::HashSet, error::Error, time::Duration};
+
+use futures_util::StreamExt as _;
+use redis::aio::ConnectionManager as RedisConnectionManager;
+use tokio::{sync::watch, task::JoinHandle};
+use twilight_gateway::{EventType, EventTypeFlags};
+
+use tulpje_common::event_subscriptions::{self, CHANGED_CHANNEL};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// subscriptions are also reloaded this often, in case they were changed
+/// without publishing to [`CHANGED_CHANNEL`]
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// the events handlers subscribed to, kept up to date by [`listen`], cheap to
+/// clone
+#[derive(Clone)]
+pub(crate) struct EventFilter {
+    receiver: watch::Receiver<Option<EventTypeFlags>>,
+}
+
+impl EventFilter {
+    /// a filter that forwards every event
+    #[cfg(test)]
+    pub(crate) fn unfiltered() -> Self {
+        let (_, receiver) = watch::channel(None);
+        Self { receiver }
+    }
+
+    /// whether any handler needs events of `kind`, everything is forwarded
+    /// until handlers subscribed
+    pub(crate) fn wants(&self, kind: Option<EventTypeFlags>) -> bool {
+        match (*self.receiver.borrow(), kind) {
+            (Some(wanted), Some(kind)) => wanted.intersects(kind),
+            _ => true,
+        }
+    }
+}
+
+/// load the subscriptions and keep them up to date in the background
+pub(crate) async fn listen(
+    client: redis::Client,
+    redis: RedisConnectionManager,
+) -> Result<(EventFilter, JoinHandle<()>), Box<dyn Error>> {
+    let (sender, receiver) = watch::channel(load(&redis).await?);
+
+    let handle = tokio::spawn(async move {
+        loop {
+            if let Err(err) = subscribe(&client, &redis, &sender).await {
+                tracing::warn!("event subscription updates failed: {}", err);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    Ok((EventFilter { receiver }, handle))
+}
+
+async fn subscribe(
+    client: &redis::Client,
+    redis: &RedisConnectionManager,
+    sender: &watch::Sender<Option<EventTypeFlags>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(CHANGED_CHANNEL).await?;
+
+    // catch up on changes made while we weren't subscribed
+    reload(redis, sender).await?;
+
+    let mut messages = pubsub.on_message();
+    let mut refresh = tokio::time::interval_at(
+        tokio::time::Instant::now() + REFRESH_INTERVAL,
+        REFRESH_INTERVAL,
+    );
+    loop {
+        tokio::select! {
+            message = messages.next() => {
+                if message.is_none() {
+                    return Err("event subscription updates closed".into());
+                }
+            },
+            _ = refresh.tick() => {},
+        }
+
+        if let Err(err) = reload(redis, sender).await {
+            tracing::warn!("error reloading event subscriptions: {}", err);
+        }
+    }
+}
+
+async fn reload(
+    redis: &RedisConnectionManager,
+    sender: &watch::Sender<Option<EventTypeFlags>>,
+) -> Result<(), Box<dyn Error>> {
+    let wanted = load(redis).await?;
+
+    sender.send_if_modified(|current| {
+        if *current == wanted {
+            return false;
+        }
+
+        tracing::info!("event subscriptions changed: {:?}", wanted);
+        *current = wanted;
+        true
+    });
+
+    Ok(())
+}
+
+async fn load(redis: &RedisConnectionManager) -> Result<Option<EventTypeFlags>, Box<dyn Error>> {
+    Ok(event_subscriptions::load(redis).await?.and_then(to_flags))
+}
+
+/// `None` when any of the events is unknown, e.g. when a handler is newer than
+/// the gateway, in which case we'd rather forward too much than too little
+fn to_flags(events: HashSet<String>) -> Option<EventTypeFlags> {
+    events.iter().try_fold(
+        EventTypeFlags::empty(),
+        |flags, name| match EventType::try_from(name.as_str()) {
+            Ok(event_type) => Some(flags | EventTypeFlags::from(event_type)),
+            Err(_) => {
+                tracing::warn!("unknown event {:?} in subscriptions, not filtering", name);
+                None
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_flags() {
+        let flags = to_flags(HashSet::from([
+            "MESSAGE_CREATE".to_string(),
+            "INTERACTION_CREATE".to_string(),
+        ]));
+        assert_eq!(
+            flags,
+            Some(EventTypeFlags::MESSAGE_CREATE | EventTypeFlags::INTERACTION_CREATE),
+            "event names should map to their flags"
+        );
+
+        assert_eq!(
+            to_flags(HashSet::from(["NOT_AN_EVENT".to_string()])),
+            None,
+            "unknown events should disable filtering"
+        );
+    }
+}
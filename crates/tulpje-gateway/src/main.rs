@@ -1,38 +1,102 @@
-use std::{env, error::Error, time::Duration};
+use std::{error::Error, time::Duration};
 
-use redis::aio::ConnectionManagerConfig;
+use redis::aio::{ConnectionManager as RedisConnectionManager, ConnectionManagerConfig};
 use tokio::signal::unix::SignalKind;
+use tokio_util::sync::CancellationToken;
 use twilight_gateway::Intents;
-use twilight_model::gateway::{
-    payload::outgoing::{identify::IdentifyProperties, update_presence::UpdatePresencePayload},
-    presence::{Activity, MinimalActivity, Status},
+use twilight_model::{
+    gateway::{
+        payload::outgoing::update_presence::UpdatePresencePayload,
+        presence::{Activity, MinimalActivity, Status},
+    },
+    oauth::ApplicationFlags,
 };
 
 use reconnecting_amqp::{AmqpHandle, ConnectionArguments};
-use tulpje_common::version;
+use tulpje_common::{intents, runtime_config, sharding, version};
 
+mod cluster;
 mod config;
+mod event_filter;
 mod metrics;
 mod parsed_event;
+mod resharding;
+mod session;
 mod shard_manager;
 mod shard_reporter;
 
+use cluster::{ClusterContext, ShardCluster};
 use config::Config;
-use shard_reporter::ShardReporterHandle;
+use resharding::{Resharder, ShardingMessage};
 
-use crate::shard_manager::ShardManagerHandle;
+/// used until a handler published the intents its modules need
+const DEFAULT_INTENTS: Intents = Intents::GUILDS
+    .union(Intents::GUILD_EMOJIS_AND_STICKERS)
+    .union(Intents::GUILD_MESSAGES)
+    .union(Intents::GUILD_MESSAGE_REACTIONS)
+    .union(Intents::MESSAGE_CONTENT);
 
-async fn get_intents() -> Result<Intents, Box<dyn Error>> {
-    let mut intents = Intents::empty()
-        | Intents::GUILDS
-        | Intents::GUILD_EMOJIS_AND_STICKERS
-        | Intents::GUILD_MESSAGES
-        | Intents::GUILD_MESSAGE_REACTIONS;
+/// intents that have to be enabled for the application before they can be
+/// requested, with the application flags that enable them
+const PRIVILEGED_INTENTS: [(Intents, ApplicationFlags); 3] = [
+    (
+        Intents::GUILD_MEMBERS,
+        ApplicationFlags::GATEWAY_GUILD_MEMBERS
+            .union(ApplicationFlags::GATEWAY_GUILD_MEMBERS_LIMITED),
+    ),
+    (
+        Intents::GUILD_PRESENCES,
+        ApplicationFlags::GATEWAY_PRESENCE.union(ApplicationFlags::GATEWAY_PRESENCE_LIMITED),
+    ),
+    (
+        Intents::MESSAGE_CONTENT,
+        ApplicationFlags::GATEWAY_MESSAGE_CONTENT
+            .union(ApplicationFlags::GATEWAY_MESSAGE_CONTENT_LIMITED),
+    ),
+];
 
-    if std::env::var("TULPJE_MESSAGE_CONTENT").unwrap_or_else(|_| "true".to_string()) == "true" {
-        intents |= Intents::MESSAGE_CONTENT;
+/// the intents the handlers' modules need, privileged intents that aren't
+/// enabled for the application are left out, discord would close the
+/// connection otherwise
+async fn get_intents(
+    config: &Config,
+    redis: &RedisConnectionManager,
+) -> Result<Intents, Box<dyn Error>> {
+    let mut intents = match intents::load(redis).await? {
+        Some(bits) => Intents::from_bits_truncate(bits),
+        None => {
+            tracing::info!("no handler published its intents yet, using defaults");
+            DEFAULT_INTENTS
+        }
+    };
+
+    if !runtime_config::load(redis).await?.message_content {
+        intents.remove(Intents::MESSAGE_CONTENT);
+    }
+
+    let client = twilight_http::Client::builder()
+        .proxy(config.discord_proxy.clone(), true)
+        .token(config.discord_token.clone())
+        .ratelimiter(None)
+        .build();
+    let flags = client
+        .current_user_application()
+        .await?
+        .model()
+        .await?
+        .flags
+        .unwrap_or_else(ApplicationFlags::empty);
+    for (intent, flag) in PRIVILEGED_INTENTS {
+        if intents.contains(intent) && !flags.intersects(flag) {
+            tracing::warn!(
+                "privileged intent {:?} requested, but not enabled for the application, leaving it out",
+                intent
+            );
+            intents.remove(intent);
+        }
     }
 
+    tracing::info!("intents: {:?}", intents);
     Ok(intents)
 }
 
@@ -54,7 +118,7 @@ async fn main() {
         .expect("error setting tls provider");
 
     // create config from environment vars
-    let config = Config::load().expect("error loading config from env");
+    let mut config = Config::load().expect("error loading config from env");
 
     // create AMQP connection
     let mut amqp = AmqpHandle::try_from_str(
@@ -66,7 +130,8 @@ async fn main() {
     amqp.wait_start().await.expect("couldn't connect to amqp");
 
     // create the redis connection
-    let redis_client = redis::Client::open(config.redis_url).expect("error initialising redis");
+    let redis_client =
+        redis::Client::open(config.redis_url.as_str()).expect("error initialising redis");
     let redis = redis_client
         .get_connection_manager_with_config(
             ConnectionManagerConfig::new()
@@ -76,70 +141,128 @@ async fn main() {
         .await
         .expect("error creating connection manager");
 
+    // clusters run the sharding of the last reshard, if there was one
+    let sharding = match config.cluster_id {
+        Some(_) => sharding::load(&redis)
+            .await
+            .expect("error loading sharding"),
+        None => None,
+    };
+    if let Some(sharding) = &sharding {
+        config.apply_sharding(sharding);
+    }
+    let shard_range = config
+        .shard_range()
+        .expect("error determining shards to run");
+    let process_name = config
+        .process_name()
+        .expect("error determining process name");
+
     // set-up metrics
     tracing::info!("installing metrics collector and exporter...");
-    metrics::install(config.metrics_listen_addr, redis.clone(), config.shard_id)
-        .expect("error setting up metrics");
-
-    let desired_intents = get_intents().await.expect("error calculating intents");
-
-    // create the shard
-    tracing::info!("shard: {}, total: {}", config.shard_id, config.shard_count);
-    let shard_config = twilight_gateway::ConfigBuilder::new(config.discord_token, desired_intents)
-        .presence(create_presence())
-        .identify_properties(IdentifyProperties {
-            browser: "tulpje".into(),
-            device: "tulpje".into(),
-            os: std::env::consts::OS.into(),
-        })
-        .build();
-    let shard_id = twilight_gateway::ShardId::new_checked(config.shard_id, config.shard_count)
-        .expect("error constructing shard ID");
-    let shard = twilight_gateway::Shard::with_config(shard_id, shard_config);
-
-    // create shard reporter
-    let (shard_reporter_join, mut shard_reporter) =
-        ShardReporterHandle::new(redis.clone(), shard_id.number());
-
-    // create shard manager
-    let (shard_manager_join, mut shard_manager) =
-        ShardManagerHandle::new(shard, amqp.sender(), shard_reporter.clone());
-
-    // initialisation done, ratelimit on session_limit
-    tracing::info!("waiting for gateway queue...");
-    reqwest::get(format!(
-        "{}?shard={}",
-        config.discord_gateway_queue,
-        shard_id.number()
-    ))
+    metrics::install(
+        config.metrics_listen_addr,
+        redis.clone(),
+        process_name.clone(),
+    )
+    .expect("error setting up metrics");
+
+    // only forward the events handlers subscribed to
+    let (event_filter, event_filter_handle) =
+        event_filter::listen(redis_client.clone(), redis.clone())
+            .await
+            .expect("error loading event subscriptions");
+
+    let desired_intents = get_intents(&config, &redis)
+        .await
+        .expect("error calculating intents");
+
+    // create the shards, every shard gets its own manager and reporter, but
+    // they share the amqp and redis connections
+    tracing::info!("shards: {}, total: {}", shard_range, config.shard_count);
+    let ctx = ClusterContext {
+        token: config.discord_token.clone(),
+        intents: desired_intents,
+        gateway_queue: config.discord_gateway_queue.clone(),
+        amqp_tx: amqp.sender(),
+        event_filter,
+        redis: redis.clone(),
+        process_name,
+    };
+    let mut cluster = ShardCluster::new(
+        &ctx,
+        sharding.map_or(0, |sharding| sharding.generation),
+        shard_range,
+        config.shard_count,
+        true,
+    )
     .await
-    .expect("error waiting for gateway queue");
+    .expect("error creating shards");
 
-    if let Err(err) = shard_manager.start() {
-        tracing::error!(?err, "error starting shard, shutting down ...");
-        shard_manager.shutdown();
-    }
+    let (mut sharding_messages, sharding_messages_handle) =
+        resharding::listen(redis_client.clone());
+    let mut resharder = Resharder::new(config.cluster_id);
+    let mut sharding_sync = tokio::time::interval_at(
+        tokio::time::Instant::now() + resharding::SYNC_INTERVAL,
+        resharding::SYNC_INTERVAL,
+    );
 
-    tokio::spawn(async move {
-        tokio::select! {
-            _ = sigint.recv() => {},
-            _ = sigterm.recv() => {},
-        }
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            tokio::select! {
+                _ = sigint.recv() => {},
+                _ = sigterm.recv() => {},
+            }
 
-        tracing::info!("shutting down...");
-        shard_manager.shutdown();
+            tracing::info!("shutting down...");
+            shutdown.cancel();
+        }
     });
 
-    if let Err(err) = shard_manager_join.await {
-        tracing::error!("error joining main_handle: {err}");
+    // initialisation done, start the shards as the gateway queue allows
+    if let Err(err) = cluster.start(&ctx, &shutdown).await {
+        tracing::error!(?err, "error starting shards, shutting down ...");
+        shutdown.cancel();
     }
 
-    shard_reporter.shutdown();
-    tracing::trace!("waiting for shard reporter to exit...");
-    if let Err(err) = shard_reporter_join.await {
-        tracing::error!("error joining shard reporter: {err}");
+    // run until we're told to stop, or a shard stops by itself, in which case
+    // we stop the others too so the process gets restarted
+    loop {
+        tokio::select! {
+            () = shutdown.cancelled() => break,
+            () = cluster.stopped() => {
+                tracing::error!("shard manager exited, shutting down...");
+                break;
+            },
+            Some(message) = sharding_messages.recv() => {
+                let res = match message {
+                    ShardingMessage::Command(command) => {
+                        resharder.handle(command, &ctx, &mut cluster, &shutdown).await
+                    }
+                    ShardingMessage::Subscribed => {
+                        resharder.sync(&ctx, &mut cluster, &shutdown).await
+                    }
+                };
+                if let Err(err) = res {
+                    tracing::error!(?err, "error resharding");
+                }
+            },
+            _ = sharding_sync.tick() => {
+                if let Err(err) = resharder.sync(&ctx, &mut cluster, &shutdown).await {
+                    tracing::error!(?err, "error resharding");
+                }
+            },
+            () = resharder.report_ready(&redis), if resharder.has_unreported() => {},
+        }
     }
 
+    sharding_messages_handle.abort();
+    event_filter_handle.abort();
+    resharder.shutdown().await;
+    cluster.shutdown(&redis).await;
+
     amqp.shutdown();
     tracing::trace!("waiting for amqp to exit...");
     if let Err(err) = amqp.join().await {
@@ -149,7 +272,7 @@ async fn main() {
     tracing::info!("cleanup finished, exiting...")
 }
 
-fn create_presence() -> UpdatePresencePayload {
+pub(crate) fn create_presence() -> UpdatePresencePayload {
     let state = format!(" Version: {}", version!());
 
     let mut activity: Activity = MinimalActivity {
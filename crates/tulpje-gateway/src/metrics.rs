@@ -9,14 +9,14 @@ use tulpje_common::{metrics::MetricsListenAddr, version};
 pub(crate) fn install(
     listen_addr: MetricsListenAddr,
     redis: RedisConnectionManager,
-    shard_id: u32,
+    process_name: String,
 ) -> Result<(), Box<dyn Error>> {
     // install metrics collector and exporter
     tulpje_common::metrics::install(
         PrometheusBuilder::new(),
         listen_addr,
         redis,
-        format!("gateway-{}", shard_id),
+        process_name,
         version!(),
     )?;
 
@@ -15,24 +15,38 @@ pub(crate) const WANTED_EVENTS: EventTypeFlags = EventTypeFlags::from_bits_trunc
 
 pub(crate) struct ParsedEvent {
     pub(crate) forward: bool,
+    /// `None` for close frames
+    pub(crate) kind: Option<EventTypeFlags>,
     pub(crate) name: Option<String>,
     pub(crate) event: Option<Event>,
     pub(crate) text: Option<String>,
 }
 
 impl ParsedEvent {
-    pub(crate) fn from_event(forward: bool, event: Event, text: Option<String>) -> Self {
+    pub(crate) fn from_event(
+        forward: bool,
+        kind: Option<EventTypeFlags>,
+        event: Event,
+        text: Option<String>,
+    ) -> Self {
         Self {
             forward,
+            kind,
             name: event.kind().name().map(String::from),
             event: Some(event),
             text,
         }
     }
 
-    pub(crate) fn from_text(forward: bool, name: Option<String>, text: String) -> Self {
+    pub(crate) fn from_text(
+        forward: bool,
+        kind: EventTypeFlags,
+        name: Option<String>,
+        text: String,
+    ) -> Self {
         Self {
             forward,
+            kind: Some(kind),
             name,
             event: None,
             text: Some(text),
@@ -41,7 +55,12 @@ impl ParsedEvent {
 
     pub(crate) fn from_message(value: Message) -> Result<Self, Box<dyn Error>> {
         match value {
-            Message::Close(frame) => Ok(Self::from_event(false, Event::GatewayClose(frame), None)),
+            Message::Close(frame) => Ok(Self::from_event(
+                false,
+                None,
+                Event::GatewayClose(frame),
+                None,
+            )),
             Message::Text(text) => {
                 let Some(deserialize) = GatewayEventDeserializer::from_json(&text) else {
                     return Err(format!("couldn't deserialize event: {text}").into());
@@ -61,6 +80,7 @@ impl ParsedEvent {
                     let mut json_deserializer = serde_json::Deserializer::from_str(&text);
                     Ok(Self::from_event(
                         opcode == OpCode::Dispatch,
+                        Some(event_type_flags),
                         deserialize
                             .deserialize(&mut json_deserializer)
                             .map_err(|err| format!("error deserialising event: {err},{text}"))?
@@ -70,6 +90,7 @@ impl ParsedEvent {
                 } else {
                     Ok(Self::from_text(
                         opcode == OpCode::Dispatch,
+                        event_type_flags,
                         event_type.map(String::from),
                         text,
                     ))
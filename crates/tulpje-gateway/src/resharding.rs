@@ -0,0 +1,255 @@
+use std::{error::Error, time::Duration};
+
+use futures_util::{StreamExt as _, future};
+use redis::aio::ConnectionManager as RedisConnectionManager;
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use tulpje_common::sharding::{self, COMMAND_CHANNEL, ShardingCommand};
+
+use crate::{
+    cluster::{ClusterContext, ShardCluster},
+    config::ShardRange,
+};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// how often to check for reshards we missed, see [`Resharder::sync`]
+pub(crate) const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+pub(crate) enum ShardingMessage {
+    Command(ShardingCommand),
+    /// (re)subscribed, commands sent while we weren't subscribed are lost
+    Subscribed,
+}
+
+/// receive the commands sent by `tulpje-manager` while resharding
+pub(crate) fn listen(
+    client: redis::Client,
+) -> (UnboundedReceiver<ShardingMessage>, JoinHandle<()>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    let handle = tokio::spawn(async move {
+        loop {
+            if let Err(err) = subscribe(&client, &sender).await {
+                tracing::warn!("sharding command subscription failed: {}", err);
+            }
+            if sender.is_closed() {
+                break;
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    (receiver, handle)
+}
+
+async fn subscribe(
+    client: &redis::Client,
+    sender: &UnboundedSender<ShardingMessage>,
+) -> Result<(), Box<dyn Error>> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(COMMAND_CHANNEL).await?;
+    if sender.send(ShardingMessage::Subscribed).is_err() {
+        return Ok(());
+    }
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let payload: String = message.get_payload()?;
+        let command = match serde_json::from_str::<ShardingCommand>(&payload) {
+            Ok(command) => command,
+            Err(err) => {
+                tracing::warn!("invalid sharding command {:?}: {}", payload, err);
+                continue;
+            }
+        };
+
+        if sender.send(ShardingMessage::Command(command)).is_err() {
+            return Ok(());
+        }
+    }
+
+    Err("sharding command subscription closed".into())
+}
+
+/// moves a cluster's shards over to a new sharding, as told by the manager
+pub(crate) struct Resharder {
+    cluster_id: Option<u32>,
+    pending: Option<ShardCluster>,
+    /// whether we've reported the pending shards as ready
+    reported: bool,
+}
+
+impl Resharder {
+    pub(crate) fn new(cluster_id: Option<u32>) -> Self {
+        Self {
+            cluster_id,
+            pending: None,
+            reported: false,
+        }
+    }
+
+    pub(crate) fn has_unreported(&self) -> bool {
+        self.pending.is_some() && !self.reported
+    }
+
+    /// wait for the pending shards to be ready, and tell the manager
+    pub(crate) async fn report_ready(&mut self, redis: &RedisConnectionManager) {
+        let Some(pending) = &mut self.pending else {
+            return future::pending().await;
+        };
+
+        let ready = pending.wait_ready().await;
+        self.reported = true;
+        if !ready {
+            tracing::warn!(
+                "shards {} of generation {} stopped before they were ready",
+                pending.range,
+                pending.generation
+            );
+            return;
+        }
+
+        tracing::info!(
+            "shards {} of generation {} ready",
+            pending.range,
+            pending.generation
+        );
+        for shard_id in pending.shard_ids() {
+            if let Err(err) =
+                sharding::mark_ready(redis, pending.generation, shard_id.number()).await
+            {
+                tracing::error!("error marking shard {} ready: {}", shard_id.number(), err);
+            }
+        }
+    }
+
+    pub(crate) async fn handle(
+        &mut self,
+        command: ShardingCommand,
+        ctx: &ClusterContext,
+        active: &mut ShardCluster,
+        shutdown: &CancellationToken,
+    ) -> Result<(), Box<dyn Error>> {
+        match command {
+            ShardingCommand::Prepare(sharding) => {
+                let Some(cluster_id) = self.cluster_id else {
+                    tracing::warn!("not running a cluster, ignoring reshard");
+                    return Ok(());
+                };
+                if sharding.generation <= active.generation {
+                    return Ok(());
+                }
+
+                // a newer reshard replaces one that didn't finish
+                if let Some(pending) = self.pending.take() {
+                    pending.retire().await;
+                }
+
+                let range =
+                    ShardRange::cluster(cluster_id, sharding.cluster_size, sharding.shard_count)?;
+                tracing::info!(
+                    "preparing shards {} of {} for generation {}",
+                    range,
+                    sharding.shard_count,
+                    sharding.generation
+                );
+
+                // keep track of the shards before starting them, so an abort
+                // also stops them when starting fails halfway
+                self.reported = false;
+                let pending = self.pending.insert(
+                    ShardCluster::new(ctx, sharding.generation, range, sharding.shard_count, false)
+                        .await?,
+                );
+                pending.start(ctx, shutdown).await?;
+            }
+            ShardingCommand::Commit { generation } => {
+                if let Some(pending) = self
+                    .pending
+                    .take_if(|pending| pending.generation == generation)
+                {
+                    tracing::info!(
+                        "switching from shards {} to {} of generation {}",
+                        active.range,
+                        pending.range,
+                        generation
+                    );
+                    pending.forward(true);
+                    std::mem::replace(active, pending).retire().await;
+                }
+
+                // also confirm when we switched earlier, the manager resends
+                // commits until every cluster confirmed
+                if let Some(cluster_id) = self.cluster_id
+                    && active.generation == generation
+                {
+                    sharding::mark_retired(&ctx.redis, generation, cluster_id).await?;
+                }
+            }
+            ShardingCommand::Abort { generation } => {
+                if let Some(pending) = self
+                    .pending
+                    .take_if(|pending| pending.generation == generation)
+                {
+                    tracing::info!("reshard to generation {} aborted", generation);
+                    pending.retire().await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// catch up on commands we missed, commits the sharding the manager stored
+    /// when our shards are of an older generation
+    pub(crate) async fn sync(
+        &mut self,
+        ctx: &ClusterContext,
+        active: &mut ShardCluster,
+        shutdown: &CancellationToken,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.cluster_id.is_none() {
+            return Ok(());
+        }
+        let Some(sharding) = sharding::load(&ctx.redis).await? else {
+            return Ok(());
+        };
+        if sharding.generation <= active.generation {
+            return Ok(());
+        }
+
+        if !self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| pending.generation == sharding.generation)
+        {
+            tracing::warn!(
+                "missed the preparation of generation {}, starting its shards",
+                sharding.generation
+            );
+            self.handle(ShardingCommand::Prepare(sharding), ctx, active, shutdown)
+                .await?;
+        }
+
+        tracing::warn!("missed the commit of generation {}", sharding.generation);
+        self.handle(
+            ShardingCommand::Commit {
+                generation: sharding.generation,
+            },
+            ctx,
+            active,
+            shutdown,
+        )
+        .await
+    }
+
+    pub(crate) async fn shutdown(self) {
+        if let Some(pending) = self.pending {
+            pending.retire().await;
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::{error::Error, time::Duration};
+
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+use serde::{Deserialize, Serialize};
+use twilight_gateway::{Intents, Session, ShardId};
+
+/// discord doesn't keep sessions around for long, so there's no point in
+/// trying to resume older ones
+const SESSION_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// what a shard needs to resume its session after a restart
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct SavedSession {
+    pub(crate) session: Session,
+    pub(crate) resume_url: Option<String>,
+    /// resuming keeps the intents the session was identified with, sessions
+    /// saved without them are never resumed
+    #[serde(default = "Intents::empty")]
+    pub(crate) intents: Intents,
+}
+
+/// sessions belong to a shard of a specific shard count
+fn key(shard_id: ShardId) -> String {
+    format!(
+        "tulpje:gateway:session:{}:{}",
+        shard_id.number(),
+        shard_id.total()
+    )
+}
+
+pub(crate) async fn store(
+    redis: &RedisConnectionManager,
+    shard_id: ShardId,
+    session: &SavedSession,
+) -> Result<(), Box<dyn Error>> {
+    redis
+        .clone()
+        .set_ex::<_, _, ()>(
+            key(shard_id),
+            serde_json::to_string(session)?,
+            SESSION_TTL.as_secs(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// take the saved session of a shard, sessions are only used once, if
+/// resuming fails the shard identifies instead
+pub(crate) async fn take(
+    redis: &RedisConnectionManager,
+    shard_id: ShardId,
+) -> Result<Option<SavedSession>, Box<dyn Error>> {
+    let session: Option<String> = redis.clone().get_del(key(shard_id)).await?;
+
+    Ok(match session {
+        Some(session) => Some(serde_json::from_str(&session)?),
+        None => None,
+    })
+}
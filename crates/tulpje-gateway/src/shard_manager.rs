@@ -1,27 +1,37 @@
 use futures_util::StreamExt as _;
 use std::error::Error;
 use tokio::{
-    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    sync::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        watch,
+    },
     task::JoinHandle,
 };
 use tokio_util::sync::CancellationToken;
 use tulpje_common::DiscordEvent;
-use twilight_gateway::{CloseFrame, Message, Shard};
+use twilight_gateway::{CloseFrame, Event, Message, Shard, ShardId};
 
 use crate::{
+    event_filter::EventFilter,
     metrics,
     parsed_event::ParsedEvent,
+    session::SavedSession,
     shard_reporter::{ReporterEvent, ShardReporterHandle},
 };
 
 pub(crate) enum ShardManagerMessage {
     Start,
+    /// start or stop forwarding events to amqp
+    Forward(bool),
+    /// disconnect without keeping the session around for resuming
+    Retire,
 }
 
 #[derive(Clone)]
 pub(crate) struct ShardManagerHandle {
     sender: mpsc::UnboundedSender<ShardManagerMessage>,
     shutdown: CancellationToken,
+    ready: watch::Receiver<bool>,
 }
 
 #[derive(PartialEq)]
@@ -32,24 +42,64 @@ enum ShardState {
 }
 
 impl ShardManagerHandle {
+    /// `resume_url` is where the session the shard was configured with, if
+    /// any, should be resumed
     pub(crate) fn new(
         shard: Shard,
+        resume_url: Option<String>,
         amqp_tx: UnboundedSender<Vec<u8>>,
+        filter: EventFilter,
         reporter: ShardReporterHandle,
-    ) -> (JoinHandle<()>, Self) {
+        forward: bool,
+    ) -> (JoinHandle<Option<(ShardId, SavedSession)>>, Self) {
         let (sender, receiver) = mpsc::unbounded_channel();
+        let (ready_tx, ready) = watch::channel(false);
         let shutdown = CancellationToken::new();
 
-        let mut shard_mgr = ShardManager::new(receiver, shard, amqp_tx, reporter, shutdown.clone());
+        let mut shard_mgr = ShardManager::new(
+            receiver,
+            shard,
+            resume_url,
+            amqp_tx,
+            filter,
+            reporter,
+            shutdown.clone(),
+            forward,
+            ready_tx,
+        );
         let handle = tokio::spawn(async move { shard_mgr.run().await });
 
-        (handle, Self { sender, shutdown })
+        (
+            handle,
+            Self {
+                sender,
+                shutdown,
+                ready,
+            },
+        )
     }
 
     pub(crate) fn start(&self) -> Result<(), mpsc::error::SendError<ShardManagerMessage>> {
         self.sender.send(ShardManagerMessage::Start)
     }
 
+    pub(crate) fn forward(
+        &self,
+        forward: bool,
+    ) -> Result<(), mpsc::error::SendError<ShardManagerMessage>> {
+        self.sender.send(ShardManagerMessage::Forward(forward))
+    }
+
+    pub(crate) fn retire(&self) -> Result<(), mpsc::error::SendError<ShardManagerMessage>> {
+        self.sender.send(ShardManagerMessage::Retire)
+    }
+
+    /// wait until the shard received READY, or resumed
+    pub(crate) async fn wait_ready(&mut self) -> Result<(), watch::error::RecvError> {
+        self.ready.wait_for(|ready| *ready).await?;
+        Ok(())
+    }
+
     pub(crate) fn shutdown(&mut self) {
         self.shutdown.cancel();
     }
@@ -59,30 +109,51 @@ pub(crate) struct ShardManager {
     receiver: UnboundedReceiver<ShardManagerMessage>,
     shard: Shard,
     amqp_tx: UnboundedSender<Vec<u8>>,
+    filter: EventFilter,
     reporter: ShardReporterHandle,
     shutdown: CancellationToken,
     state: ShardState,
+    forward: bool,
+    ready: watch::Sender<bool>,
+    resume_url: Option<String>,
+    /// closed to resume the session later, instead of for good
+    resumable: bool,
 }
 
 impl ShardManager {
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "only called from ShardManagerHandle::new"
+    )]
     fn new(
         receiver: mpsc::UnboundedReceiver<ShardManagerMessage>,
         shard: Shard,
+        resume_url: Option<String>,
         amqp_tx: UnboundedSender<Vec<u8>>,
+        filter: EventFilter,
         reporter: ShardReporterHandle,
         shutdown: CancellationToken,
+        forward: bool,
+        ready: watch::Sender<bool>,
     ) -> Self {
         Self {
             receiver,
             shard,
             amqp_tx,
+            filter,
             reporter,
             shutdown,
             state: ShardState::Stopped,
+            forward,
+            ready,
+            resume_url,
+            resumable: false,
         }
     }
 
-    async fn run(&mut self) {
+    /// run until the shard is closed, returns the session when it can be
+    /// resumed after a restart
+    async fn run(&mut self) -> Option<(ShardId, SavedSession)> {
         tracing::info!("ShardManager started...");
 
         loop {
@@ -93,6 +164,20 @@ impl ShardManager {
                             self.state = ShardState::Running;
                             tracing::info!("Shard started...");
                         },
+                        ShardManagerMessage::Forward(forward) => {
+                            self.forward = forward;
+                        },
+                        ShardManagerMessage::Retire => {
+                            tracing::info!("retiring shard {}...", self.shard.id().number());
+                            self.forward = false;
+                            self.resumable = false;
+                            if self.state == ShardState::Running {
+                                self.shard.close(CloseFrame::NORMAL);
+                                self.state = ShardState::Stopping;
+                            } else {
+                                break;
+                            }
+                        },
                     };
                 },
                 msg = self.shard.next(), if self.state != ShardState::Stopped => {
@@ -113,9 +198,16 @@ impl ShardManager {
                         }
                     }
                 },
+                // shards that never started have nothing to disconnect, the
+                // session they were configured with can still be resumed
+                () = self.shutdown.cancelled(), if self.state == ShardState::Stopped => {
+                    self.resumable = true;
+                    break;
+                },
                 () = self.shutdown.cancelled(), if self.state == ShardState::Running => {
                     tracing::info!("disconnecting from Discord...");
                     self.shard.close(CloseFrame::RESUME);
+                    self.resumable = true;
                     self.state = ShardState::Stopping;
                 },
                 else => break
@@ -123,6 +215,16 @@ impl ShardManager {
         }
 
         tracing::info!("ShardManager stopped...");
+
+        if !self.resumable {
+            return None;
+        }
+        let saved_session = SavedSession {
+            session: self.shard.session()?.clone(),
+            resume_url: self.resume_url.clone(),
+            intents: self.shard.config().intents(),
+        };
+        Some((self.shard.id(), saved_session))
     }
 
     fn handle_message(&mut self, message: Message) -> Result<bool, Box<dyn Error>> {
@@ -140,13 +242,28 @@ impl ShardManager {
         );
 
         if let Some(event) = event.event {
-            self.reporter
+            if let Event::Ready(ready) = &event {
+                self.resume_url = Some(ready.resume_gateway_url.clone());
+            }
+            if matches!(event, Event::Ready(_) | Event::Resumed) {
+                self.ready.send_replace(true);
+            }
+
+            // don't let reporting get in the way of forwarding, the reporter
+            // is also gone already while a shard is being retired
+            if let Err(err) = self
+                .reporter
                 .try_send(ReporterEvent::from_event(event, self.shard.latency()))
-                .map_err(|err| format!("error sending message to ShardManager: {err}"))?;
+            {
+                tracing::warn!(?err, "error sending message to ShardReporter");
+            }
         }
 
         if let Some(text) = event.text
             && event.forward
+            && self.forward
+            // don't bother serialising events no handler needs
+            && self.filter.wants(event.kind)
         {
             let event = DiscordEvent::new(self.shard.id().number(), text);
 
@@ -167,3 +284,75 @@ impl ShardManager {
         Ok(should_stop)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use twilight_gateway::{ConfigBuilder, Intents, Session};
+
+    use super::*;
+
+    async fn shutdown_before_start(
+        shard: Shard,
+        resume_url: Option<String>,
+    ) -> Option<(ShardId, SavedSession)> {
+        let (amqp_tx, _amqp_rx) = mpsc::unbounded_channel();
+        let (join, mut manager) = ShardManagerHandle::new(
+            shard,
+            resume_url,
+            amqp_tx,
+            EventFilter::unfiltered(),
+            ShardReporterHandle::detached(),
+            true,
+        );
+
+        manager.shutdown();
+        tokio::time::timeout(Duration::from_secs(5), join)
+            .await
+            .expect("shard manager didn't stop")
+            .expect("error joining shard manager")
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_before_start() {
+        let shard = Shard::new(ShardId::ONE, "token".into(), Intents::empty());
+        assert!(
+            shutdown_before_start(shard, None).await.is_none(),
+            "shards that never started should stop without a session"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_before_start_keeps_session() {
+        let config = ConfigBuilder::new("token".into(), Intents::GUILDS)
+            .session(Session::new(42, "session".into()))
+            .build();
+        let shard = Shard::with_config(ShardId::ONE, config);
+
+        let saved = shutdown_before_start(shard, Some("wss://resume".into())).await;
+        let Some((shard_id, saved_session)) = saved else {
+            panic!("shards that never started should hand back their session");
+        };
+        assert_eq!(
+            shard_id,
+            ShardId::ONE,
+            "session should be for the same shard"
+        );
+        assert_eq!(
+            saved_session.session.id(),
+            "session",
+            "session should be the one the shard was configured with"
+        );
+        assert_eq!(
+            saved_session.resume_url.as_deref(),
+            Some("wss://resume"),
+            "resume url should be kept"
+        );
+        assert_eq!(
+            saved_session.intents,
+            Intents::GUILDS,
+            "session should keep the intents it was identified with"
+        );
+    }
+}
@@ -1,18 +1,22 @@
 use std::{
     collections::HashSet,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
 use tokio::{sync::mpsc, task::JoinHandle};
 use tokio_util::sync::CancellationToken;
-use twilight_gateway::{Event, EventType, EventTypeFlags, Latency};
+use twilight_gateway::{Event, EventType, EventTypeFlags, Latency, ShardId};
 
 use tulpje_common::shard_state::ShardState;
 use twilight_model::gateway::payload::incoming::{GuildCreate, GuildDelete, Hello, Ready};
 
 use crate::metrics::track_guild_count;
 
+/// how long the guilds of a shard are kept after it stopped, for resuming the
+/// shard's session, see [`crate::session`]
+const GUILDS_TTL: Duration = Duration::from_secs(15 * 60);
+
 pub(crate) const SHARD_REPORTER_EVENTS: EventTypeFlags = EventTypeFlags::from_bits_truncate(
     EventTypeFlags::GATEWAY_HEARTBEAT_ACK.bits()
         | EventTypeFlags::GATEWAY_HELLO.bits()
@@ -48,17 +52,48 @@ impl ReporterEvent {
 pub(crate) struct ShardReporterHandle {
     sender: mpsc::Sender<ReporterEvent>,
     shutdown: CancellationToken,
+    retired: CancellationToken,
 }
 impl ShardReporterHandle {
-    pub(crate) fn new(redis: RedisConnectionManager, shard_id: u32) -> (JoinHandle<()>, Self) {
+    pub(crate) fn new(
+        redis: RedisConnectionManager,
+        shard_id: ShardId,
+        process_name: String,
+    ) -> (JoinHandle<()>, Self) {
         // TODO: Configure channel size?
         let (sender, receiver) = mpsc::channel(10);
         let shutdown = CancellationToken::new();
+        let retired = CancellationToken::new();
 
-        let mut reporter = ShardReporter::new(redis, shard_id, receiver, shutdown.clone());
+        let mut reporter = ShardReporter::new(
+            redis,
+            shard_id,
+            process_name,
+            receiver,
+            shutdown.clone(),
+            retired.clone(),
+        );
         let handle = tokio::spawn(async move { reporter.run().await });
 
-        (handle, Self { sender, shutdown })
+        (
+            handle,
+            Self {
+                sender,
+                shutdown,
+                retired,
+            },
+        )
+    }
+
+    /// a handle without a reporter, events sent to it are dropped
+    #[cfg(test)]
+    pub(crate) fn detached() -> Self {
+        let (sender, _) = mpsc::channel(1);
+        Self {
+            sender,
+            shutdown: CancellationToken::new(),
+            retired: CancellationToken::new(),
+        }
     }
 
     pub(crate) fn try_send(
@@ -75,29 +110,45 @@ impl ShardReporterHandle {
     pub(crate) fn shutdown(&mut self) {
         self.shutdown.cancel();
     }
+
+    /// stop reporting for good, without saving the guilds, as nothing will
+    /// resume the shard's session
+    pub(crate) fn retire(&mut self) {
+        self.retired.cancel();
+        self.shutdown.cancel();
+    }
 }
 
 pub struct ShardReporter {
     redis: RedisConnectionManager,
     guild_ids: HashSet<u64>,
     shard: ShardState,
+    shard_total: u32,
     receiver: mpsc::Receiver<ReporterEvent>,
     shutdown: CancellationToken,
+    retired: CancellationToken,
 }
 
 impl ShardReporter {
     pub fn new(
         redis: RedisConnectionManager,
-        shard_id: u32,
+        shard_id: ShardId,
+        process_name: String,
         receiver: mpsc::Receiver<ReporterEvent>,
         shutdown: CancellationToken,
+        retired: CancellationToken,
     ) -> Self {
         Self {
             redis,
             guild_ids: HashSet::new(),
-            shard: ShardState::new(shard_id),
+            shard: ShardState {
+                process: process_name,
+                ..ShardState::new(shard_id.number())
+            },
+            shard_total: shard_id.total(),
             receiver,
             shutdown,
+            retired,
         }
     }
 
@@ -105,6 +156,11 @@ impl ShardReporter {
         let mut shutting_down = false;
         tracing::info!("ShardReporter started...");
 
+        // a resumed session doesn't send the guilds again
+        if let Err(err) = self.load_guilds().await {
+            tracing::warn!(?err, "error loading guilds of previous session");
+        }
+
         loop {
             tokio::select! {
                 Some(evt) = self.receiver.recv() => {
@@ -120,9 +176,44 @@ impl ShardReporter {
             }
         }
 
+        if !self.retired.is_cancelled()
+            && let Err(err) = self.save_guilds().await
+        {
+            tracing::warn!(?err, "error saving guilds");
+        }
+
         tracing::info!("ShardReporter stopped...");
     }
 
+    /// like sessions, guilds belong to a shard of a specific shard count
+    fn guilds_key(&self) -> String {
+        format!(
+            "tulpje:gateway:guilds:{}:{}",
+            self.shard.shard_id, self.shard_total
+        )
+    }
+
+    async fn load_guilds(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let guilds: Option<String> = self.redis.clone().get_del(self.guilds_key()).await?;
+        if let Some(guilds) = guilds {
+            self.guild_ids = serde_json::from_str(&guilds)?;
+        }
+
+        Ok(())
+    }
+
+    async fn save_guilds(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.redis
+            .clone()
+            .set_ex::<_, _, ()>(
+                self.guilds_key(),
+                serde_json::to_string(&self.guild_ids)?,
+                GUILDS_TTL.as_secs(),
+            )
+            .await
+            .map_err(|err| err.into())
+    }
+
     async fn handle_event(
         &mut self,
         evt: &ReporterEvent,
@@ -178,8 +269,8 @@ impl ShardReporter {
             ready.guilds.len()
         );
 
-        self.guild_ids
-            .extend(ready.guilds.iter().map(|g| g.id.get()));
+        // READY has all guilds, so forget any loaded from a previous session
+        self.guild_ids = ready.guilds.iter().map(|g| g.id.get()).collect();
 
         self.shard.up = true;
         self.shard.guild_count = self
@@ -196,6 +287,12 @@ impl ShardReporter {
         tracing::info!("shard {} resumed", self.shard.shard_id,);
 
         self.shard.up = true;
+        self.shard.guild_count = self
+            .guild_ids
+            .len()
+            .try_into()
+            .expect("couldn't convert len() to u64");
+        track_guild_count(self.shard.shard_id, self.shard.guild_count);
         self.shard.last_connection = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("time went backwards")
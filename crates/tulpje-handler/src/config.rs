@@ -3,6 +3,7 @@ use figment_file_provider_adapter::FileAdapter;
 use serde::{Deserialize, Serialize};
 
 use tulpje_common::metrics::MetricsListenAddr;
+use tulpje_framework::framework::{DispatchOptions, EventOrdering};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
@@ -17,6 +18,34 @@ pub struct Config {
 
     #[serde(default = "MetricsListenAddr::default")]
     pub metrics_listen_addr: MetricsListenAddr,
+
+    #[serde(default = "default_event_concurrency")]
+    pub event_concurrency: usize,
+    #[serde(default = "default_event_queue_size")]
+    pub event_queue_size: usize,
+    #[serde(default)]
+    pub event_ordering: EventOrdering,
+    #[serde(default = "default_amqp_prefetch_count")]
+    pub amqp_prefetch_count: u16,
+
+    /// users that can use owner only commands, overrides the application owners
+    #[serde(default)]
+    pub owners: Vec<u64>,
+    /// guilds owner only commands are registered in
+    #[serde(default)]
+    pub dev_guilds: Vec<u64>,
+}
+
+fn default_event_concurrency() -> usize {
+    DispatchOptions::default().concurrency
+}
+
+fn default_event_queue_size() -> usize {
+    DispatchOptions::default().queue_size
+}
+
+fn default_amqp_prefetch_count() -> u16 {
+    100
 }
 
 impl Config {
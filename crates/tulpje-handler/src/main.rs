@@ -1,7 +1,7 @@
 mod config;
 mod metrics;
 
-use std::{sync::Arc, time::Duration};
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
 
 use pkrs_fork::client::PkClient;
 use redis::aio::ConnectionManagerConfig;
@@ -11,13 +11,23 @@ use sqlx::{
 };
 use tokio::{signal::unix::SignalKind, sync::mpsc};
 use tracing::{Instrument as _, Span, log::LevelFilter};
-use tulpje_lib::context;
+use tulpje_lib::{
+    commands::{self, CommandScope},
+    context,
+    jobs::RedisJobStore,
+    leader::SchedulerLease,
+};
 use twilight_gateway::Event;
+use twilight_model::id::Id;
 
 use reconnecting_amqp::{AmqpHandle, ConnectionArguments};
 use tulpje_cache::{Cache, Config as CacheConfig, ResourceType};
-use tulpje_common::{DiscordEvent, version};
-use tulpje_framework::{Framework, Metadata, Registry, framework::Sender};
+use tulpje_common::{DiscordEvent, event_subscriptions, intents, runtime_config, version};
+use tulpje_framework::{
+    Metadata, Registry,
+    framework::{DispatchOptions, FrameworkBuilder, Sender},
+    job::Jobs,
+};
 
 use config::Config;
 
@@ -42,23 +52,41 @@ async fn main() {
     let config = Config::load().expect("error loading config");
 
     // needed for fetching recommended shard count
-    let client = Arc::new(
-        twilight_http::Client::builder()
-            .proxy(config.discord_proxy, true)
-            .token(config.discord_token)
-            .ratelimiter(None)
-            .build(),
-    );
-
-    // Get and store application id
-    let app_id = client
+    let client = twilight_http::Client::builder()
+        .proxy(config.discord_proxy, true)
+        .token(config.discord_token)
+        .ratelimiter(None)
+        .build();
+
+    // Get and store application id and owners
+    let application = client
         .current_user_application()
         .await
         .expect("error fetching application")
         .model()
         .await
-        .expect("eror decoding application")
-        .id;
+        .expect("eror decoding application");
+    let app_id = application.id;
+    let owners = if !config.owners.is_empty() {
+        config
+            .owners
+            .iter()
+            .filter_map(|id| Id::new_checked(*id))
+            .collect()
+    } else {
+        match application.team {
+            Some(team) => team
+                .members
+                .into_iter()
+                .map(|member| member.user.id)
+                .collect(),
+            None => application
+                .owner
+                .into_iter()
+                .map(|owner| owner.id)
+                .collect(),
+        }
+    };
 
     // create the redis connection
     let redis_client = redis::Client::open(config.redis_url).expect("error initialising redis");
@@ -71,6 +99,12 @@ async fn main() {
         .await
         .expect("error creating connection manager");
 
+    // load runtime config, and keep it up to date
+    let (runtime_config, runtime_config_handle) =
+        runtime_config::listen(redis_client.clone(), redis.clone())
+            .await
+            .expect("error loading runtime config");
+
     // set-up metrics
     tracing::info!("installing metrics collector and exporter...");
     metrics::install(config.metrics_listen_addr, redis.clone(), config.handler_id)
@@ -105,11 +139,12 @@ async fn main() {
         .expect("error connecting to db");
 
     // create AMQP connection
-    let (amqp_tx, mut amqp_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    // bounded so a busy handler stops acknowledging, and receiving, messages
+    let (amqp_tx, mut amqp_rx) = mpsc::channel::<Vec<u8>>(config.event_queue_size);
     // create AMQP connection
     let mut amqp = AmqpHandle::try_from_str(
         &config.rabbitmq_address,
-        ConnectionArguments::new("discord"),
+        ConnectionArguments::new("discord").prefetch_count(config.amqp_prefetch_count),
         Some(amqp_tx),
     )
     .expect("couldn't create amqp client");
@@ -133,32 +168,64 @@ async fn main() {
     // previous modules to set up
     registry.register(tulpje_mod_core::build(&registry));
 
-    // only run scheduled tasks on the "primary" handler
-    if config.handler_id != 0 {
-        registry.tasks.clear();
-    }
+    // only run handlers of guild scoped modules in guilds that enabled them
+    registry.guild_module_lookup(
+        |ctx, guild_id| Box::pin(tulpje_mod_core::enabled_modules(ctx, guild_id)),
+        None,
+    );
 
     // we don't need to mutate registry anymore after this
     let registry = Arc::new(registry);
 
-    let services = Arc::new(context::Services {
+    // tell the gateways which events we need, so they can drop the others
+    let event_types: BTreeSet<String> = registry
+        .event_types()
+        .into_iter()
+        .chain(cache.config.event_types())
+        .filter_map(|event_type| event_type.name().map(String::from))
+        .collect();
+    event_subscriptions::publish(&redis, config.handler_id, &event_types)
+        .await
+        .expect("error publishing event subscriptions");
+    intents::publish(&redis, config.handler_id, registry.intents().bits())
+        .await
+        .expect("error publishing intents");
+
+    // every handler schedules tasks, but only the one holding the lease runs them
+    let scheduler_lease = SchedulerLease::new(redis.clone(), config.handler_id);
+    let scheduler_lease_handle = scheduler_lease.start();
+
+    metrics::spawn_task_stats(
+        Arc::clone(&registry),
+        redis.clone(),
+        scheduler_lease.clone(),
+    );
+
+    // one-off jobs are shared between, and can be run by, every handler
+    let jobs = Jobs::new(RedisJobStore::new(redis.clone()));
+
+    let services = context::Services {
         handler_id: config.handler_id,
+        owners,
+        dev_guilds: config
+            .dev_guilds
+            .iter()
+            .filter_map(|id| Id::new_checked(*id))
+            .collect(),
 
         pk: Arc::new(PkClient {
             user_agent: format!("Tulpje {}", version!()),
             ..Default::default()
         }),
         cache: Arc::clone(&cache),
-        redis,
+        redis: redis.clone(),
         db,
         registry: Arc::clone(&registry),
-    });
-    let mut framework = Framework::new(
-        registry,
-        client,
-        app_id,
-        services,
-        Some(|ctx| {
+        jobs: jobs.clone(),
+        config: runtime_config,
+    };
+    let mut framework = FrameworkBuilder::new(Arc::clone(&registry), client, app_id, services)
+        .setup(|ctx| {
             Box::pin(async move {
                 // only register commands on the "primary" handler to avoid
                 // sending too many requests to discord
@@ -167,18 +234,52 @@ async fn main() {
                 }
 
                 tracing::info!("registering global commands");
-                ctx.interaction()
-                    .set_global_commands(&ctx.services.registry.global_commands())
+                commands::sync_commands(
+                    ctx.interaction(),
+                    &ctx.services.redis,
+                    CommandScope::Global,
+                    &ctx.services.registry.global_commands(),
+                )
+                .await
+                .map_err(|err| format!("error registering global commands: {}", err))?;
+
+                // resumed gateway sessions don't send GUILD_CREATE, which is where
+                // guild commands are registered otherwise, this also registers
+                // owner commands in dev guilds
+                tulpje_mod_core::sync_guild_commands(&ctx)
                     .await
-                    .map_err(|err| format!(".set_global_commands() error: {}", err))?;
+                    .map_err(|err| format!("error registering guild commands: {}", err))?;
 
                 Ok(())
             })
-        }),
-    );
+        })
+        .middleware(metrics::HandlerMetrics)
+        // check permissions first, so commands that can't run don't use up cooldowns
+        .middleware(tulpje_lib::permissions::OwnerOnly)
+        .middleware(tulpje_lib::permissions::BotPermissions)
+        .middleware(tulpje_lib::cooldown::Cooldowns)
+        .middleware(scheduler_lease.clone())
+        .jobs(jobs)
+        .error_message(|services| services.config.get().error_message.clone())
+        .dispatch(DispatchOptions {
+            concurrency: config.event_concurrency,
+            queue_size: config.event_queue_size,
+            ordering: config.event_ordering,
+        })
+        .build();
 
     framework.start().await.expect("error starting framework");
 
+    // pause/resume/run tasks when asked to through `/admin task`
+    let task_control_handle = tulpje_lib::tasks::listen(
+        redis_client.clone(),
+        redis,
+        Arc::clone(&registry),
+        framework.scheduler(),
+    );
+    // drop cached enabled modules when they're changed on any handler
+    let guild_modules_handle = tulpje_lib::guild_modules::listen(redis_client, registry);
+
     let sender = framework.sender();
     let main_handle = tokio::spawn(async move {
         loop {
@@ -219,6 +320,14 @@ async fn main() {
     }
 
     framework.shutdown().await;
+    task_control_handle.abort();
+    guild_modules_handle.abort();
+    runtime_config_handle.abort();
+
+    scheduler_lease_handle.abort();
+    if let Err(err) = scheduler_lease.release().await {
+        tracing::error!("error releasing scheduler lease: {err}");
+    }
     tracing::trace!("waiting for framework to exit...");
     if let Err(err) = framework.join().await {
         tracing::error!("error joining framework: {err}");
@@ -235,7 +344,7 @@ async fn handle_message(cache: &Cache, sender: &Sender, meta: Metadata, event: E
 
     tracing::debug!("{:?} received", event.kind());
 
-    if let Err(err) = sender.with_span(meta, event, Span::current()) {
+    if let Err(err) = sender.with_span(meta, event, Span::current()).await {
         tracing::error!("error queueing event: {err}");
     };
 }
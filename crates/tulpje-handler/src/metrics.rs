@@ -1,6 +1,16 @@
+use std::{sync::Arc, time::Duration};
+
 use metrics_exporter_prometheus::PrometheusBuilder;
-use redis::aio::ConnectionManager as RedisConnectionManager;
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
 use tulpje_common::{metrics::MetricsListenAddr, version};
+use tulpje_framework::{
+    Context, Error, Registry,
+    middleware::{BoxFuture, HandlerInfo, Middleware},
+};
+use tulpje_lib::{context::Services, leader::SchedulerLease};
+
+/// how often task stats get exported
+const TASK_STATS_INTERVAL: Duration = Duration::from_secs(10);
 
 pub(crate) fn install(
     listen_addr: MetricsListenAddr,
@@ -17,7 +27,114 @@ pub(crate) fn install(
     )?;
 
     // define metrics
-    // ..
+    metrics::describe_histogram!(
+        "handler_duration_seconds",
+        metrics::Unit::Seconds,
+        "Handler Run Time"
+    );
+    metrics::describe_counter!("handler_errors", "Handler Errors");
+    metrics::describe_counter!("task_runs", "Task Runs");
+    metrics::describe_counter!("task_failures", "Task Failures");
+    metrics::describe_counter!("task_skipped", "Task Runs Skipped Due To Overlap");
+    metrics::describe_gauge!("task_running", "Task Runs In Progress");
+    metrics::describe_gauge!(
+        "task_last_duration_seconds",
+        metrics::Unit::Seconds,
+        "Task Last Run Time"
+    );
+    metrics::describe_gauge!(
+        "task_last_finish_timestamp_seconds",
+        metrics::Unit::Seconds,
+        "Task Last Finished At"
+    );
+
+    Ok(())
+}
+
+/// records how long each handler took to run and whether it failed
+pub(crate) struct HandlerMetrics;
+
+impl<T: Clone + Send + Sync> Middleware<T> for HandlerMetrics {
+    fn after<'a>(
+        &'a self,
+        _ctx: &'a Context<T>,
+        info: &'a HandlerInfo<'a>,
+        result: &'a Result<(), Error>,
+        elapsed: Duration,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let labels = [
+                ("kind", info.kind.name().to_string()),
+                ("module", info.module.to_string()),
+            ];
+
+            metrics::histogram!("handler_duration_seconds", &labels).record(elapsed);
+            if result.is_err() {
+                metrics::counter!("handler_errors", &labels).increment(1);
+            }
+        })
+    }
+}
+
+/// periodically export task stats as metrics, and to redis so `/info tasks`
+/// works no matter which handler the command ends up on or ran the task
+pub(crate) fn spawn_task_stats(
+    registry: Arc<Registry<Services>>,
+    redis: RedisConnectionManager,
+    lease: SchedulerLease,
+) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = export_task_stats(&registry, redis.clone(), &lease).await {
+                tracing::warn!("error exporting task stats: {}", err);
+            }
+            tokio::time::sleep(TASK_STATS_INTERVAL).await;
+        }
+    });
+}
+
+async fn export_task_stats(
+    registry: &Registry<Services>,
+    mut redis: RedisConnectionManager,
+    lease: &SchedulerLease,
+) -> Result<(), Error> {
+    // tasks only run on the scheduler leader, only it has stats worth sharing,
+    // former leaders would overwrite the stats of the current one
+    let leader = lease.holds_lease().await?;
+
+    for stats in registry.task_stats() {
+        let labels = [
+            ("task", stats.name.clone()),
+            ("module", stats.module.clone()),
+        ];
+
+        metrics::counter!("task_runs", &labels).absolute(stats.runs);
+        metrics::counter!("task_failures", &labels).absolute(stats.failures);
+        metrics::counter!("task_skipped", &labels).absolute(stats.skipped);
+        metrics::gauge!("task_running", &labels).set(stats.running);
+        if let Some(duration_ms) = stats.last_duration_ms {
+            metrics::gauge!("task_last_duration_seconds", &labels)
+                .set(Duration::from_millis(duration_ms));
+        }
+        if let Some(last_finish) = stats.last_finish {
+            #[expect(
+                clippy::cast_precision_loss,
+                reason = "unix timestamps fit in an f64 for the next few million years"
+            )]
+            metrics::gauge!("task_last_finish_timestamp_seconds", &labels).set(last_finish as f64);
+        }
+
+        if !leader {
+            continue;
+        }
+        redis
+            .hset::<&str, &str, String, ()>(
+                "tulpje:task_stats",
+                &stats.name,
+                serde_json::to_string(&stats)?,
+            )
+            .await?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,89 @@
+use std::fmt::Display;
+
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+use tulpje_framework::{
+    Error,
+    command_hash::{CommandDiff, CommandHashes, hash_commands},
+};
+use twilight_http::client::InteractionClient;
+use twilight_model::{
+    application::command::Command,
+    id::{Id, marker::GuildMarker},
+};
+
+/// stored alongside the hashes after every sync, so an empty set of commands can
+/// be told apart from commands that were never synced, command hashes are keyed
+/// by `{kind}:{name}` so this can't collide with them
+const SYNCED_FIELD: &str = "synced";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    Global,
+    Guild(Id<GuildMarker>),
+}
+
+impl CommandScope {
+    fn redis_key(self) -> String {
+        format!("tulpje:commands:{self}")
+    }
+}
+
+impl Display for CommandScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Global => write!(f, "global"),
+            Self::Guild(guild_id) => write!(f, "guild:{guild_id}"),
+        }
+    }
+}
+
+/// set the commands for `scope`, but only if they changed since the last time,
+/// the hashes of the registered commands are stored in redis, when there are
+/// none stored the commands are always set, returns whether discord was called
+pub async fn sync_commands(
+    interaction: InteractionClient<'_>,
+    redis: &RedisConnectionManager,
+    scope: CommandScope,
+    commands: &[Command],
+) -> Result<bool, Error> {
+    let mut redis = redis.clone();
+    let key = scope.redis_key();
+
+    let new = hash_commands(commands)?;
+    let mut old = redis.hgetall::<_, CommandHashes>(&key).await?;
+    let synced = old.remove(SYNCED_FIELD).is_some();
+
+    let diff = CommandDiff::new(&old, &new);
+    if synced && diff.is_empty() {
+        tracing::debug!("commands for {} unchanged, not registering", scope);
+        return Ok(false);
+    }
+
+    if synced {
+        tracing::info!("commands for {} changed: {}", scope, diff);
+    } else {
+        tracing::info!("commands for {} unknown, registering", scope);
+    }
+    match scope {
+        CommandScope::Global => interaction.set_global_commands(commands).await?,
+        CommandScope::Guild(guild_id) => interaction.set_guild_commands(guild_id, commands).await?,
+    };
+
+    let mut pipe = redis::pipe();
+    pipe.atomic().del(&key).hset(&key, SYNCED_FIELD, 1);
+    if !new.is_empty() {
+        pipe.hset_multiple(&key, &new.into_iter().collect::<Vec<_>>());
+    }
+    pipe.query_async::<()>(&mut redis).await?;
+
+    Ok(true)
+}
+
+/// forget the stored hashes for `scope`, so the next sync always calls discord
+pub async fn forget_commands(
+    redis: &RedisConnectionManager,
+    scope: CommandScope,
+) -> Result<(), Error> {
+    redis.clone().del::<_, ()>(scope.redis_key()).await?;
+    Ok(())
+}
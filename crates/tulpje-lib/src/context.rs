@@ -2,13 +2,24 @@ use std::sync::Arc;
 
 use pkrs_fork::client::PkClient;
 use redis::aio::ConnectionManager as RedisConnectionManager;
+use twilight_model::id::{
+    Id,
+    marker::{GuildMarker, UserMarker},
+};
 
 use tulpje_cache::Cache;
-use tulpje_framework::{Registry, context};
+use tulpje_common::runtime_config::RuntimeConfigHandle;
+use tulpje_framework::{Registry, context, job::Jobs};
+
+use crate::settings::{GuildSettings, Settings};
 
 #[derive(Clone)]
 pub struct Services {
     pub handler_id: u32,
+    /// owner of the application, or the members of the team that owns it
+    pub owners: Vec<Id<UserMarker>>,
+    /// guilds owner only commands are registered in
+    pub dev_guilds: Vec<Id<GuildMarker>>,
 
     pub pk: Arc<PkClient>,
     pub cache: Arc<Cache>,
@@ -19,9 +30,31 @@ pub struct Services {
     // NOTE: Cloning Registry would be very expensive and clones all the internal
     //       HashMaps, etc. so we should wrap it in an Arc
     pub registry: Arc<Registry<Self>>,
+    /// schedule one-off jobs, see [`crate::jobs::RedisJobStore`]
+    pub jobs: Jobs,
+    /// settings that can change at runtime, always use the latest through
+    /// [`RuntimeConfigHandle::get`] instead of holding on to it
+    pub config: RuntimeConfigHandle,
+}
+
+impl Services {
+    pub fn is_owner(&self, user_id: Id<UserMarker>) -> bool {
+        self.owners.contains(&user_id)
+    }
+
+    pub fn is_dev_guild(&self, guild_id: Id<GuildMarker>) -> bool {
+        self.dev_guilds.contains(&guild_id)
+    }
+
+    pub fn settings<S: Settings>(&self) -> GuildSettings<S> {
+        GuildSettings::new(self.db.clone(), self.redis.clone())
+    }
 }
 
+pub type AutocompleteContext = context::AutocompleteContext<Services>;
 pub type ComponentInteractionContext = context::ComponentInteractionContext<Services>;
 pub type CommandContext = context::CommandContext<Services>;
 pub type EventContext = context::EventContext<Services>;
+pub type JobContext = context::JobContext<Services>;
+pub type ModalContext = context::ModalContext<Services>;
 pub type TaskContext = context::TaskContext<Services>;
@@ -0,0 +1,99 @@
+use redis::Script;
+use tulpje_framework::{
+    Context, Error,
+    middleware::{BoxFuture, HandlerInfo, HandlerKind, Middleware},
+};
+use twilight_model::{
+    channel::message::MessageFlags,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{context::Services, util};
+
+/// count a use, and start the period on the first one, in one go so a key is
+/// never left without expiry, also sets the expiry on keys that somehow lost
+/// theirs, returns the count and the remaining period in milliseconds
+const INCR_SCRIPT: &str = r#"
+local count = redis.call("INCR", KEYS[1])
+if count == 1 or redis.call("PTTL", KEYS[1]) < 0 then
+    redis.call("PEXPIRE", KEYS[1], ARGV[1])
+end
+return {count, redis.call("PTTL", KEYS[1])}
+"#;
+
+/// enforces the cooldowns declared on commands, counters are stored in redis
+/// so limits are shared between all handler instances
+pub struct Cooldowns;
+
+impl Middleware<Services> for Cooldowns {
+    fn before<'a>(
+        &'a self,
+        ctx: &'a Context<Services>,
+        info: &'a HandlerInfo<'a>,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            if info.kind != HandlerKind::Command {
+                return Ok(true);
+            }
+
+            let Some(interaction) = info.interaction else {
+                return Ok(true);
+            };
+            let Some(cooldown) = ctx
+                .services
+                .registry
+                .find_command(info.name)
+                .and_then(|command| command.cooldown)
+            else {
+                return Ok(true);
+            };
+
+            let key = format!(
+                "tulpje:cooldown:{}:{}",
+                info.name,
+                cooldown.bucket(interaction)
+            );
+            let mut redis = ctx.services.redis.clone();
+
+            // at least a millisecond, PEXPIRE 0 would delete the key right away
+            let period_ms = u64::try_from(cooldown.period.as_millis())?.max(1);
+            let (count, remaining_ms) = Script::new(INCR_SCRIPT)
+                .key(&key)
+                .arg(period_ms)
+                .invoke_async::<(u32, i64)>(&mut redis)
+                .await?;
+            if count <= cooldown.limit {
+                return Ok(true);
+            }
+
+            // round up, never say "0 seconds", also when the key expired in
+            // the meantime and PTTL returned a negative value
+            let remaining = u64::try_from(remaining_ms)
+                .unwrap_or(1)
+                .max(1)
+                .div_ceil(1000);
+            ctx.interaction()
+                .create_response(
+                    interaction.id,
+                    &interaction.token,
+                    &InteractionResponse {
+                        kind: InteractionResponseType::ChannelMessageWithSource,
+                        data: Some(
+                            InteractionResponseDataBuilder::new()
+                                .flags(MessageFlags::EPHEMERAL | MessageFlags::IS_COMPONENTS_V2)
+                                .components([util::warning_message(&format!(
+                                    "you're doing that too often, try again in {} second{}",
+                                    remaining,
+                                    if remaining == 1 { "" } else { "s" }
+                                ))])
+                                .build(),
+                        ),
+                    },
+                )
+                .await?;
+
+            Ok(false)
+        })
+    }
+}
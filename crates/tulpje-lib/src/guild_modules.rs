@@ -0,0 +1,64 @@
+use std::{sync::Arc, time::Duration};
+
+use futures_util::StreamExt as _;
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+use tokio::task::JoinHandle;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+use tulpje_framework::{Error, Registry};
+
+/// notified with the guild id after the modules enabled in a guild changed
+const CHANGED_CHANNEL: &str = "tulpje:guild_modules:changed";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// drop the cached enabled modules for a guild, on every handler, call this
+/// after changing them
+pub async fn invalidate<T: Clone + Send + Sync>(
+    registry: &Registry<T>,
+    redis: &RedisConnectionManager,
+    guild_id: Id<GuildMarker>,
+) -> Result<(), Error> {
+    registry.invalidate_guild_modules(guild_id).await;
+    redis
+        .clone()
+        .publish::<_, _, ()>(CHANGED_CHANNEL, guild_id.get())
+        .await?;
+
+    Ok(())
+}
+
+/// drop cached enabled modules when any handler changes them
+pub fn listen<T: Clone + Send + Sync + 'static>(
+    client: redis::Client,
+    registry: Arc<Registry<T>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = subscribe(&client, &registry).await {
+                tracing::warn!("guild module subscription failed: {}", err);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    })
+}
+
+async fn subscribe<T: Clone + Send + Sync + 'static>(
+    client: &redis::Client,
+    registry: &Registry<T>,
+) -> Result<(), Error> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(CHANGED_CHANNEL).await?;
+
+    // changes made while we weren't subscribed would otherwise stay cached
+    registry.clear_guild_modules().await;
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        match message.get_payload::<u64>().ok().and_then(Id::new_checked) {
+            Some(guild_id) => registry.invalidate_guild_modules(guild_id).await,
+            None => tracing::warn!("couldn't parse guild id from guild module change"),
+        }
+    }
+
+    Err("guild module subscription closed".into())
+}
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use redis::{Script, aio::ConnectionManager as RedisConnectionManager};
+use tulpje_framework::{
+    Error,
+    job::{Job, JobStore},
+    middleware::BoxFuture,
+};
+
+/// sorted set of job ids, scored by when they should run
+const JOBS_KEY: &str = "tulpje:jobs";
+/// sorted set of the ids of taken jobs, scored by when their lease runs out
+const LEASED_KEY: &str = "tulpje:jobs:leased";
+/// hash of job id to the serialized job
+const DATA_KEY: &str = "tulpje:jobs:data";
+
+/// take due jobs in one go, so two handlers never take the same job, jobs
+/// whose lease ran out are due again
+const TAKE_DUE_SCRIPT: &str = r#"
+local expired = redis.call("ZRANGEBYSCORE", KEYS[2], "-inf", ARGV[1])
+for _, id in ipairs(expired) do
+    redis.call("ZREM", KEYS[2], id)
+    redis.call("ZADD", KEYS[1], ARGV[1], id)
+end
+
+local ids = redis.call("ZRANGEBYSCORE", KEYS[1], "-inf", ARGV[1], "LIMIT", 0, ARGV[2])
+local jobs = {}
+for _, id in ipairs(ids) do
+    redis.call("ZREM", KEYS[1], id)
+    local job = redis.call("HGET", KEYS[3], id)
+    if job then
+        redis.call("ZADD", KEYS[2], ARGV[3], id)
+        table.insert(jobs, job)
+    end
+end
+return jobs
+"#;
+
+/// job store shared by all handlers, taken jobs are leased until they're
+/// acknowledged or retried, if their handler crashes they're run again once
+/// the lease runs out
+#[derive(Clone)]
+pub struct RedisJobStore {
+    redis: RedisConnectionManager,
+}
+
+impl RedisJobStore {
+    pub fn new(redis: RedisConnectionManager) -> Self {
+        Self { redis }
+    }
+}
+
+impl JobStore for RedisJobStore {
+    fn push<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let id = job.id.to_string();
+            redis::pipe()
+                .atomic()
+                .hset(DATA_KEY, &id, serde_json::to_string(job)?)
+                .zadd(JOBS_KEY, &id, job.at.timestamp_millis())
+                .query_async::<()>(&mut self.redis.clone())
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    fn take_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: usize,
+    ) -> BoxFuture<'_, Result<Vec<Job>, Error>> {
+        Box::pin(async move {
+            let jobs = Script::new(TAKE_DUE_SCRIPT)
+                .key(JOBS_KEY)
+                .key(LEASED_KEY)
+                .key(DATA_KEY)
+                .arg(now.timestamp_millis())
+                .arg(limit)
+                .arg(lease_until.timestamp_millis())
+                .invoke_async::<Vec<String>>(&mut self.redis.clone())
+                .await?;
+
+            Ok(jobs
+                .into_iter()
+                .filter_map(|job| match serde_json::from_str::<Job>(&job) {
+                    Ok(job) => Some(job),
+                    Err(err) => {
+                        tracing::warn!("skipping job that couldn't be parsed: {}", err);
+                        None
+                    }
+                })
+                .collect())
+        })
+    }
+
+    fn ack<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let id = job.id.to_string();
+            redis::pipe()
+                .atomic()
+                .zrem(LEASED_KEY, &id)
+                .hdel(DATA_KEY, &id)
+                .query_async::<()>(&mut self.redis.clone())
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    fn retry<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let id = job.id.to_string();
+            redis::pipe()
+                .atomic()
+                .zrem(LEASED_KEY, &id)
+                .hset(DATA_KEY, &id, serde_json::to_string(job)?)
+                .zadd(JOBS_KEY, &id, job.at.timestamp_millis())
+                .query_async::<()>(&mut self.redis.clone())
+                .await?;
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use redis::{
+    AsyncCommands as _, ExistenceCheck, Script, SetExpiry, SetOptions,
+    aio::ConnectionManager as RedisConnectionManager,
+};
+use tokio::task::JoinHandle;
+use tulpje_framework::{
+    Context, Error,
+    middleware::{BoxFuture, HandlerInfo, HandlerKind, Middleware},
+};
+use uuid::Uuid;
+
+const LEASE_KEY: &str = "tulpje:scheduler:leader";
+/// how long a lease is valid without being renewed
+const LEASE_TTL: Duration = Duration::from_secs(30);
+/// how often the lease is renewed, or taken if nobody holds it
+const RENEW_INTERVAL: Duration = Duration::from_secs(10);
+
+/// only extend/delete the lease if we're still the one holding it
+const RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+end
+return 0
+"#;
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+struct LeaseState {
+    redis: RedisConnectionManager,
+    id: String,
+    leader: AtomicBool,
+}
+
+/// redis lease deciding which handler runs scheduled tasks, every handler
+/// competes for it and whoever holds it runs the tasks, if the leader goes away
+/// its lease expires and another handler takes over
+///
+/// as middleware it skips task runs on handlers that aren't the leader
+#[derive(Clone)]
+pub struct SchedulerLease {
+    state: Arc<LeaseState>,
+}
+
+impl SchedulerLease {
+    pub fn new(redis: RedisConnectionManager, handler_id: u32) -> Self {
+        Self {
+            state: Arc::new(LeaseState {
+                redis,
+                id: format!("handler-{}:{}", handler_id, Uuid::now_v7()),
+                leader: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.state.leader.load(Ordering::Relaxed)
+    }
+
+    /// check with redis whether we still hold the lease, [`Self::is_leader`]
+    /// can lag behind until the next renewal, e.g. when this process was paused
+    /// long enough for the lease to expire and be taken by another handler
+    pub async fn holds_lease(&self) -> Result<bool, Error> {
+        if !self.is_leader() {
+            return Ok(false);
+        }
+
+        let holder = self
+            .state
+            .redis
+            .clone()
+            .get::<_, Option<String>>(LEASE_KEY)
+            .await?;
+        let leader = holder.as_deref() == Some(self.state.id.as_str());
+        self.set_leader(leader);
+
+        Ok(leader)
+    }
+
+    /// keep trying to take, or renew, the lease in the background
+    pub fn start(&self) -> JoinHandle<()> {
+        let lease = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = lease.refresh().await {
+                    // we can't be sure we still hold the lease if redis is unreachable,
+                    // and it'll expire by itself anyway
+                    tracing::warn!("error refreshing scheduler lease: {}", err);
+                    lease.set_leader(false);
+                }
+                tokio::time::sleep(RENEW_INTERVAL).await;
+            }
+        })
+    }
+
+    async fn refresh(&self) -> Result<(), Error> {
+        let mut redis = self.state.redis.clone();
+        let ttl_ms = u64::try_from(LEASE_TTL.as_millis())?;
+
+        if self.is_leader() {
+            let renewed = Script::new(RENEW_SCRIPT)
+                .key(LEASE_KEY)
+                .arg(&self.state.id)
+                .arg(ttl_ms)
+                .invoke_async::<i64>(&mut redis)
+                .await?;
+            self.set_leader(renewed == 1);
+        } else {
+            let acquired = redis
+                .set_options::<_, _, Option<String>>(
+                    LEASE_KEY,
+                    &self.state.id,
+                    SetOptions::default()
+                        .conditional_set(ExistenceCheck::NX)
+                        .with_expiration(SetExpiry::PX(ttl_ms)),
+                )
+                .await?;
+            self.set_leader(acquired.is_some());
+        }
+
+        Ok(())
+    }
+
+    fn set_leader(&self, leader: bool) {
+        if self.state.leader.swap(leader, Ordering::Relaxed) != leader {
+            if leader {
+                tracing::info!("became scheduler leader ({})", self.state.id);
+            } else {
+                tracing::info!("no longer scheduler leader ({})", self.state.id);
+            }
+        }
+    }
+
+    /// give up the lease so another handler can take over right away
+    pub async fn release(&self) -> Result<(), Error> {
+        if !self.is_leader() {
+            return Ok(());
+        }
+
+        self.set_leader(false);
+        Script::new(RELEASE_SCRIPT)
+            .key(LEASE_KEY)
+            .arg(&self.state.id)
+            .invoke_async::<i64>(&mut self.state.redis.clone())
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl<T: Clone + Send + Sync> Middleware<T> for SchedulerLease {
+    fn before<'a>(
+        &'a self,
+        _ctx: &'a Context<T>,
+        info: &'a HandlerInfo<'a>,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            if info.kind != HandlerKind::Task || self.holds_lease().await? {
+                return Ok(true);
+            }
+
+            tracing::trace!("not scheduler leader, skipping task {}", info.name);
+            Ok(false)
+        })
+    }
+}
@@ -1,4 +1,12 @@
+pub mod commands;
 pub mod context;
+pub mod cooldown;
 pub mod db_id;
+pub mod guild_modules;
+pub mod jobs;
+pub mod leader;
+pub mod permissions;
 pub mod responses;
+pub mod settings;
+pub mod tasks;
 pub mod util;
@@ -0,0 +1,183 @@
+use tulpje_framework::{
+    Context, Error,
+    middleware::{BoxFuture, HandlerInfo, HandlerKind, Middleware},
+};
+use twilight_model::{
+    channel::message::MessageFlags,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{context::Services, util};
+
+/// only lets bot owners use commands marked owner only, and their autocomplete,
+/// they're only registered in dev guilds but anyone in those guilds could
+/// still see them
+pub struct OwnerOnly;
+
+impl Middleware<Services> for OwnerOnly {
+    fn before<'a>(
+        &'a self,
+        ctx: &'a Context<Services>,
+        info: &'a HandlerInfo<'a>,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            if info.kind != HandlerKind::Command && info.kind != HandlerKind::Autocomplete {
+                return Ok(true);
+            }
+
+            let Some(interaction) = info.interaction else {
+                return Ok(true);
+            };
+            if !ctx
+                .services
+                .registry
+                .find_command(info.name)
+                .is_some_and(|command| command.owner_only)
+            {
+                return Ok(true);
+            }
+            if interaction
+                .author_id()
+                .is_some_and(|user_id| ctx.services.is_owner(user_id))
+            {
+                return Ok(true);
+            }
+
+            tracing::debug!(
+                "{:?} tried to use owner only command /{}",
+                interaction.author_id(),
+                info.name
+            );
+            let response = if info.kind == HandlerKind::Autocomplete {
+                InteractionResponse {
+                    kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+                    data: Some(InteractionResponseDataBuilder::new().choices([]).build()),
+                }
+            } else {
+                InteractionResponse {
+                    kind: InteractionResponseType::ChannelMessageWithSource,
+                    data: Some(
+                        InteractionResponseDataBuilder::new()
+                            .flags(MessageFlags::EPHEMERAL | MessageFlags::IS_COMPONENTS_V2)
+                            .components([util::warning_message(
+                                "### Missing Permissions\nonly the bot owner can do this",
+                            )])
+                            .build(),
+                    ),
+                }
+            };
+            ctx.interaction()
+                .create_response(interaction.id, &interaction.token, &response)
+                .await?;
+
+            Ok(false)
+        })
+    }
+}
+
+/// checks the bot permissions declared on commands before running them, so
+/// users get told what's missing instead of the command failing halfway
+pub struct BotPermissions;
+
+impl BotPermissions {
+    /// only commands are checked, autocomplete can't be answered with a
+    /// message, and it would recompute the permissions on every keystroke
+    fn checks(kind: HandlerKind) -> bool {
+        kind == HandlerKind::Command
+    }
+}
+
+impl Middleware<Services> for BotPermissions {
+    fn before<'a>(
+        &'a self,
+        ctx: &'a Context<Services>,
+        info: &'a HandlerInfo<'a>,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            if !Self::checks(info.kind) {
+                return Ok(true);
+            }
+
+            let Some(interaction) = info.interaction else {
+                return Ok(true);
+            };
+            let Some(required) = ctx
+                .services
+                .registry
+                .find_command(info.name)
+                .map(|command| command.bot_permissions)
+                .filter(|permissions| !permissions.is_empty())
+            else {
+                return Ok(true);
+            };
+            // permissions only apply in guilds
+            let (Some(guild_id), Some(channel_id)) = (
+                interaction.guild_id,
+                interaction.channel.as_ref().map(|channel| channel.id),
+            ) else {
+                return Ok(true);
+            };
+
+            let (client, cache) = (&ctx.client, &ctx.services.cache);
+            let bot_id = util::get_current_user_id(client, cache).await?;
+            let channel = util::get_channel(client, cache, channel_id).await?;
+            let permissions =
+                util::channel_permissions(client, cache, guild_id, bot_id, &channel).await?;
+
+            let missing = required.difference(permissions);
+            if missing.is_empty() {
+                return Ok(true);
+            }
+
+            tracing::debug!(
+                "bot is missing {:?} for /{} in {}",
+                missing,
+                info.name,
+                channel_id
+            );
+            ctx.interaction()
+                .create_response(
+                    interaction.id,
+                    &interaction.token,
+                    &InteractionResponse {
+                        kind: InteractionResponseType::ChannelMessageWithSource,
+                        data: Some(
+                            InteractionResponseDataBuilder::new()
+                                .flags(MessageFlags::EPHEMERAL | MessageFlags::IS_COMPONENTS_V2)
+                                .components([util::warning_message(&format!(
+                                    "### Missing Permissions\nbot needs the {} in <#{}> to do this, ask a server admin to grant them",
+                                    util::format_permissions(missing),
+                                    channel_id
+                                ))])
+                                .build(),
+                        ),
+                    },
+                )
+                .await?;
+
+            Ok(false)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bot_permissions_checks() {
+        assert!(
+            BotPermissions::checks(HandlerKind::Command),
+            "commands should be checked"
+        );
+        assert!(
+            !BotPermissions::checks(HandlerKind::Autocomplete),
+            "autocomplete should be let through"
+        );
+        assert!(
+            !BotPermissions::checks(HandlerKind::Component),
+            "components don't declare bot permissions"
+        );
+    }
+}
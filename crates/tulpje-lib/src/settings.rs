@@ -0,0 +1,206 @@
+use std::{marker::PhantomData, time::Duration};
+
+use redis::{
+    AsyncCommands as _, ExistenceCheck, SetExpiry, SetOptions,
+    aio::ConnectionManager as RedisConnectionManager,
+};
+use serde_json::{Map, Value};
+use sqlx::types::Json;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+use tulpje_framework::Error;
+pub use tulpje_framework::settings::{Schema, Setting, SettingKind, Settings};
+
+use crate::db_id::DbId;
+
+/// how long settings stay cached in redis, writes update the cache so this
+/// only bounds how long unused settings take up memory
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// typed access to the settings of a module, see [`crate::context::Services::settings`]
+pub struct GuildSettings<T: Settings> {
+    db: sqlx::PgPool,
+    redis: RedisConnectionManager,
+    _settings: PhantomData<fn() -> T>,
+}
+
+impl<T: Settings> GuildSettings<T> {
+    pub fn new(db: sqlx::PgPool, redis: RedisConnectionManager) -> Self {
+        Self {
+            db,
+            redis,
+            _settings: PhantomData,
+        }
+    }
+
+    /// settings of the guild, with defaults for anything it hasn't set
+    pub async fn get(&self, guild_id: Id<GuildMarker>) -> Result<T, Error> {
+        let settings = load(&self.db, &self.redis, T::MODULE, guild_id).await?;
+
+        serde_json::from_value(Value::Object(settings)).map_err(|err| {
+            format!(
+                "invalid {} settings for guild {}: {}",
+                T::MODULE,
+                guild_id,
+                err
+            )
+            .into()
+        })
+    }
+
+    pub async fn set(&self, guild_id: Id<GuildMarker>, settings: &T) -> Result<(), Error> {
+        let Value::Object(settings) = serde_json::to_value(settings)? else {
+            return Err(format!("{} settings aren't a json object", T::MODULE).into());
+        };
+
+        store(&self.db, &self.redis, T::MODULE, guild_id, &settings).await
+    }
+
+    /// go back to the defaults
+    pub async fn reset(&self, guild_id: Id<GuildMarker>) -> Result<(), Error> {
+        store(&self.db, &self.redis, T::MODULE, guild_id, &Map::new()).await
+    }
+}
+
+fn cache_key(module: &str, guild_id: Id<GuildMarker>) -> String {
+    format!("tulpje:settings:{}:{}", guild_id, module)
+}
+
+/// the settings a guild stored for `module`, without defaults, read through
+/// the redis cache
+pub async fn load(
+    db: &sqlx::PgPool,
+    redis: &RedisConnectionManager,
+    module: &str,
+    guild_id: Id<GuildMarker>,
+) -> Result<Map<String, Value>, Error> {
+    let mut redis = redis.clone();
+    let key = cache_key(module, guild_id);
+
+    if let Some(cached) = redis.get::<_, Option<String>>(&key).await? {
+        return Ok(serde_json::from_str(&cached)?);
+    }
+
+    let settings = sqlx::query_scalar::<_, Json<Map<String, Value>>>(
+        "SELECT settings FROM guild_settings WHERE guild_id = $1 AND module = $2",
+    )
+    .bind(i64::from(DbId(guild_id)))
+    .bind(module)
+    .fetch_optional(db)
+    .await?
+    .map(|Json(settings)| settings)
+    .unwrap_or_default();
+
+    // cache guilds without settings too, that's most of them, but don't
+    // overwrite settings stored after we read them
+    redis
+        .set_options::<_, _, ()>(
+            &key,
+            serde_json::to_string(&settings)?,
+            SetOptions::default()
+                .conditional_set(ExistenceCheck::NX)
+                .with_expiration(SetExpiry::EX(CACHE_TTL.as_secs())),
+        )
+        .await?;
+
+    Ok(settings)
+}
+
+/// replace the settings a guild stored for `module`
+pub async fn store(
+    db: &sqlx::PgPool,
+    redis: &RedisConnectionManager,
+    module: &str,
+    guild_id: Id<GuildMarker>,
+    settings: &Map<String, Value>,
+) -> Result<(), Error> {
+    if settings.is_empty() {
+        sqlx::query("DELETE FROM guild_settings WHERE guild_id = $1 AND module = $2")
+            .bind(i64::from(DbId(guild_id)))
+            .bind(module)
+            .execute(db)
+            .await?;
+    } else {
+        sqlx::query(
+            "INSERT INTO guild_settings (guild_id, module, settings, updated_at) VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (guild_id, module) DO UPDATE SET settings = EXCLUDED.settings, updated_at = NOW()",
+        )
+        .bind(i64::from(DbId(guild_id)))
+        .bind(module)
+        .bind(Json(settings))
+        .execute(db)
+        .await?;
+    }
+
+    cache(redis, module, guild_id, settings).await
+}
+
+/// change a single setting, settings changed at the same time aren't lost
+pub async fn set_key(
+    db: &sqlx::PgPool,
+    redis: &RedisConnectionManager,
+    module: &str,
+    guild_id: Id<GuildMarker>,
+    key: &str,
+    value: &Value,
+) -> Result<(), Error> {
+    let Json(settings) = sqlx::query_scalar::<_, Json<Map<String, Value>>>(
+        "INSERT INTO guild_settings (guild_id, module, settings, updated_at) VALUES ($1, $2, jsonb_build_object($3::text, $4::jsonb), NOW())
+        ON CONFLICT (guild_id, module) DO UPDATE SET settings = guild_settings.settings || EXCLUDED.settings, updated_at = NOW()
+        RETURNING settings",
+    )
+    .bind(i64::from(DbId(guild_id)))
+    .bind(module)
+    .bind(key)
+    .bind(Json(value))
+    .fetch_one(db)
+    .await?;
+
+    cache(redis, module, guild_id, &settings).await
+}
+
+/// reset a single setting to its default, settings changed at the same time
+/// aren't lost
+pub async fn remove_key(
+    db: &sqlx::PgPool,
+    redis: &RedisConnectionManager,
+    module: &str,
+    guild_id: Id<GuildMarker>,
+    key: &str,
+) -> Result<(), Error> {
+    let settings = sqlx::query_scalar::<_, Json<Map<String, Value>>>(
+        "UPDATE guild_settings SET settings = settings - $3, updated_at = NOW()
+        WHERE guild_id = $1 AND module = $2
+        RETURNING settings",
+    )
+    .bind(i64::from(DbId(guild_id)))
+    .bind(module)
+    .bind(key)
+    .fetch_optional(db)
+    .await?
+    .map(|Json(settings)| settings)
+    .unwrap_or_default();
+
+    cache(redis, module, guild_id, &settings).await
+}
+
+/// update instead of invalidating after writes, reads only fill in the cache
+/// when it's empty, so a read that started before a write can't put back the
+/// old settings
+async fn cache(
+    redis: &RedisConnectionManager,
+    module: &str,
+    guild_id: Id<GuildMarker>,
+    settings: &Map<String, Value>,
+) -> Result<(), Error> {
+    redis
+        .clone()
+        .set_ex::<_, _, ()>(
+            cache_key(module, guild_id),
+            serde_json::to_string(settings)?,
+            CACHE_TTL.as_secs(),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::{sync::Arc, time::Duration};
+
+use futures_util::StreamExt as _;
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tulpje_framework::{Error, Registry, framework::SchedulerSender};
+
+const CONTROL_CHANNEL: &str = "tulpje:tasks:control";
+/// names of the tasks that are paused, so handlers that start later pause them too
+const PAUSED_KEY: &str = "tulpje:tasks:paused";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// runtime change to a scheduled task, broadcast to every handler so whichever
+/// one holds the scheduler lease acts on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "task", rename_all = "lowercase")]
+pub enum TaskCommand {
+    /// run the task right away, outside of its schedule
+    Run(String),
+    /// stop scheduling the task until it's resumed
+    Pause(String),
+    Resume(String),
+}
+
+impl TaskCommand {
+    pub fn task(&self) -> &str {
+        match self {
+            Self::Run(name) | Self::Pause(name) | Self::Resume(name) => name,
+        }
+    }
+}
+
+/// send a command to the schedulers of all handlers
+pub async fn send(redis: &RedisConnectionManager, command: &TaskCommand) -> Result<(), Error> {
+    let mut redis = redis.clone();
+
+    match command {
+        TaskCommand::Pause(name) => redis.sadd::<_, _, ()>(PAUSED_KEY, name).await?,
+        TaskCommand::Resume(name) => redis.srem::<_, _, ()>(PAUSED_KEY, name).await?,
+        TaskCommand::Run(_) => {}
+    }
+
+    redis
+        .publish::<_, _, ()>(CONTROL_CHANNEL, serde_json::to_string(command)?)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn paused(redis: &RedisConnectionManager) -> Result<Vec<String>, Error> {
+    Ok(redis.clone().smembers(PAUSED_KEY).await?)
+}
+
+/// apply task commands sent by any handler to our own scheduler, should be
+/// started after the framework so paused tasks don't get scheduled again
+pub fn listen<T: Clone + Send + Sync + 'static>(
+    client: redis::Client,
+    redis: RedisConnectionManager,
+    registry: Arc<Registry<T>>,
+    scheduler: SchedulerSender<T>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = subscribe(&client, &redis, &registry, &scheduler).await {
+                tracing::warn!("task control subscription failed: {}", err);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    })
+}
+
+async fn subscribe<T: Clone + Send + Sync + 'static>(
+    client: &redis::Client,
+    redis: &RedisConnectionManager,
+    registry: &Registry<T>,
+    scheduler: &SchedulerSender<T>,
+) -> Result<(), Error> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(CONTROL_CHANNEL).await?;
+
+    // catch up on anything sent before we (re)subscribed, only after subscribing
+    // so nothing sent in between gets lost
+    let paused = paused(redis).await?;
+    for (name, handler) in &registry.tasks {
+        let command = match (paused.contains(name), handler.is_paused()) {
+            (true, false) => TaskCommand::Pause(name.clone()),
+            (false, true) => TaskCommand::Resume(name.clone()),
+            _ => continue,
+        };
+
+        if let Err(err) = apply(registry, scheduler, command) {
+            tracing::warn!("error applying task command: {}", err);
+        }
+    }
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let command = match serde_json::from_slice::<TaskCommand>(message.get_payload_bytes()) {
+            Ok(command) => command,
+            Err(err) => {
+                tracing::warn!("couldn't parse task command: {}", err);
+                continue;
+            }
+        };
+
+        tracing::info!("received task command {:?}", command);
+        if let Err(err) = apply(registry, scheduler, command) {
+            tracing::warn!("error applying task command: {}", err);
+        }
+    }
+
+    Err("task control subscription closed".into())
+}
+
+fn apply<T: Clone + Send + Sync + 'static>(
+    registry: &Registry<T>,
+    scheduler: &SchedulerSender<T>,
+    command: TaskCommand,
+) -> Result<(), Error> {
+    let handler = registry
+        .tasks
+        .get(command.task())
+        .ok_or_else(|| format!("unknown task {}", command.task()))?;
+
+    match command {
+        TaskCommand::Run(_) => scheduler.run_task(handler.clone()),
+        TaskCommand::Pause(name) => scheduler.disable_task(name),
+        TaskCommand::Resume(_) => scheduler.enable_task(handler.clone()),
+    }
+    .map_err(|err| format!("error sending task command to scheduler: {}", err))?;
+
+    Ok(())
+}
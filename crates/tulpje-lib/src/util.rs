@@ -87,20 +87,38 @@ pub async fn get_member_roles(
     Ok(roles)
 }
 
-/// check whether the specified user has the required permissions and
-/// communicates to the end user if it doesn't.
-///
-/// returns a boolean indicating whether the permissions were present
-pub async fn handle_permissions(
-    ctx: &CommandContext,
+/// the channel from the cache, or from discord if it isn't cached
+pub async fn get_channel(
+    client: &Client,
+    cache: &Cache,
+    channel_id: Id<ChannelMarker>,
+) -> Result<Channel, Error> {
+    if let Some(channel) = cache.channels.get(&channel_id).await? {
+        return Ok(channel);
+    }
+
+    Ok(client.channel(channel_id).await?.model().await?)
+}
+
+pub async fn get_current_user_id(client: &Client, cache: &Cache) -> Result<Id<UserMarker>, Error> {
+    if let Some(user) = cache.current_user.get().await? {
+        return Ok(user.id);
+    }
+
+    Ok(client.current_user().await?.model().await?.id)
+}
+
+/// effective permissions of the user in `channel`, threads use the permission
+/// overwrites of their parent channel
+pub async fn channel_permissions(
+    client: &Client,
+    cache: &Cache,
     guild_id: Id<GuildMarker>,
     user_id: Id<UserMarker>,
     channel: &Channel,
-    permissions: Permissions,
-) -> Result<bool, Error> {
-    let everyone_role = get_everyone_role(&ctx.client, &ctx.services.cache, guild_id).await?;
-    let member_roles =
-        get_member_roles(&ctx.client, &ctx.services.cache, user_id, guild_id).await?;
+) -> Result<Permissions, Error> {
+    let everyone_role = get_everyone_role(client, cache, guild_id).await?;
+    let member_roles = get_member_roles(client, cache, user_id, guild_id).await?;
     let member_role_permissions: Vec<_> =
         member_roles.iter().map(|r| (r.id, r.permissions)).collect();
 
@@ -111,30 +129,28 @@ pub async fn handle_permissions(
         &member_role_permissions,
     );
 
-    // calculate effective permissions
-    let calculated_permissions = calculator.in_channel(
-        channel.kind,
-        &channel.permission_overwrites.clone().unwrap_or_default(),
-    );
-
-    // calculate missing permissions
-    let missing_permissions = permissions.difference(calculated_permissions);
+    let overwrites = match channel.parent_id {
+        Some(parent_id) if channel.kind.is_thread() => get_channel(client, cache, parent_id)
+            .await?
+            .permission_overwrites
+            .unwrap_or_default(),
+        _ => channel.permission_overwrites.clone().unwrap_or_default(),
+    };
 
-    // return if user has all permissions
-    if missing_permissions.is_empty() {
-        return Ok(true);
-    }
+    Ok(calculator.in_channel(channel.kind, &overwrites))
+}
 
-    // get permission names
-    let mut permission_names: Vec<_> = missing_permissions.iter_names().map(|(k, _)| k).collect();
+/// e.g. "MANAGE_ROLES and SEND_MESSAGES permissions", `permissions` shouldn't
+/// be empty
+pub fn format_permissions(permissions: Permissions) -> String {
+    let mut permission_names: Vec<_> = permissions.iter_names().map(|(k, _)| k).collect();
 
     // pop the last one for string formatting
-    let last_permission = permission_names
-        .pop()
-        .expect("missing_permissions isn't empty so shouldn't fail");
+    let Some(last_permission) = permission_names.pop() else {
+        return "no permissions".to_string();
+    };
 
-    // format the permission string
-    let permissions_string = if !permission_names.is_empty() {
+    if !permission_names.is_empty() {
         format!(
             "{} and {} permissions",
             permission_names.join(", "),
@@ -142,13 +158,38 @@ pub async fn handle_permissions(
         )
     } else {
         format!("{} permission", last_permission)
-    };
+    }
+}
+
+/// check whether the specified user has the required permissions and
+/// communicates to the end user if it doesn't.
+///
+/// returns a boolean indicating whether the permissions were present
+pub async fn handle_permissions(
+    ctx: &CommandContext,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    channel: &Channel,
+    permissions: Permissions,
+) -> Result<bool, Error> {
+    // calculate effective permissions
+    let calculated_permissions =
+        channel_permissions(&ctx.client, &ctx.services.cache, guild_id, user_id, channel).await?;
+
+    // calculate missing permissions
+    let missing_permissions = permissions.difference(calculated_permissions);
+
+    // return if user has all permissions
+    if missing_permissions.is_empty() {
+        return Ok(true);
+    }
 
     // inform the user
     responses::error(
         ctx,
         &format!(
-            "### Error\nbot is missing {permissions_string} in <#{}>",
+            "### Error\nbot is missing {} in <#{}>",
+            format_permissions(missing_permissions),
             channel.id
         ),
     )
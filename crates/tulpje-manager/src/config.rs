@@ -0,0 +1,34 @@
+use figment::{Figment, providers::Env};
+use figment_file_provider_adapter::FileAdapter;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Config {
+    pub discord_token: String,
+    pub discord_proxy: String,
+    pub redis_url: String,
+
+    /// how many gateway clusters are deployed, shards are spread over them
+    pub cluster_count: u32,
+    /// use this shard count instead of the one discord recommends
+    #[serde(default)]
+    pub shard_count: Option<u32>,
+    /// seconds to wait for the new shards to be ready before giving up
+    #[serde(default = "default_ready_timeout")]
+    pub ready_timeout: u64,
+    /// reshard even if the shard count stays the same
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn default_ready_timeout() -> u64 {
+    10 * 60
+}
+
+impl Config {
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Figment::new()
+            .merge(FileAdapter::wrap(Env::raw()))
+            .extract()?)
+    }
+}
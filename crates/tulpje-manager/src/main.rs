@@ -0,0 +1,276 @@
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
+
+use tulpje_common::{
+    sharding::{self, Sharding, ShardingCommand},
+    version,
+};
+
+mod config;
+
+use config::Config;
+
+/// how often to check whether the new shards are ready
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[tokio::main]
+async fn main() {
+    // set-up logging
+    tulpje_common::logging::init();
+    tracing::info!("starting tulpje-manager {} ...", version!());
+
+    // configure tls
+    rustls::crypto::aws_lc_rs::default_provider()
+        .install_default()
+        .expect("error setting tls provider");
+
+    // create config from environment vars
+    let config = Config::load().expect("error loading config from env");
+
+    // create the redis connection
+    let redis = redis::Client::open(config.redis_url.as_str())
+        .expect("error initialising redis")
+        .get_connection_manager()
+        .await
+        .expect("error creating connection manager");
+
+    if let Err(err) = reshard(&config, &redis).await {
+        tracing::error!("resharding failed: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// move the gateway clusters over to the shard count discord recommends, new
+/// shards are connected next to the old ones, and only replace them once
+/// every new shard is ready, see [`ShardingCommand`]
+async fn reshard(config: &Config, redis: &RedisConnectionManager) -> Result<(), Box<dyn Error>> {
+    if config.cluster_count == 0 {
+        return Err("CLUSTER_COUNT can't be 0".into());
+    }
+
+    let client = twilight_http::Client::builder()
+        .proxy(config.discord_proxy.clone(), true)
+        .token(config.discord_token.clone())
+        .ratelimiter(None)
+        .build();
+    let info = client.gateway().authed().await?.model().await?;
+    let max_concurrency = u32::from(info.session_start_limit.max_concurrency);
+
+    let current = sharding::load(redis).await?;
+    let shard_count = config
+        .shard_count
+        .unwrap_or_else(|| recommended_shard_count(info.shards, max_concurrency));
+    tracing::info!(
+        "discord recommends {} shards (max concurrency {}), current: {}, new: {}",
+        info.shards,
+        max_concurrency,
+        current.map_or_else(
+            || "unknown".into(),
+            |current| current.shard_count.to_string()
+        ),
+        shard_count
+    );
+
+    if !config.force && current.is_some_and(|current| current.shard_count == shard_count) {
+        tracing::info!("shard count unchanged, nothing to do");
+        return Ok(());
+    }
+    if info.session_start_limit.remaining < shard_count {
+        return Err(format!(
+            "only {} identifies left, need {}, try again in {}s",
+            info.session_start_limit.remaining,
+            shard_count,
+            Duration::from_millis(info.session_start_limit.reset_after).as_secs()
+        )
+        .into());
+    }
+
+    let new = Sharding {
+        generation: current.map_or(1, |current| current.generation + 1),
+        shard_count,
+        cluster_size: cluster_size(shard_count, config.cluster_count)?,
+    };
+    tracing::info!(
+        "preparing generation {}, {} shards in clusters of {}",
+        new.generation,
+        new.shard_count,
+        new.cluster_size
+    );
+    sharding::send(redis, &ShardingCommand::Prepare(new)).await?;
+
+    if let Err(err) = wait_ready(redis, &new, Duration::from_secs(config.ready_timeout)).await {
+        tracing::warn!("aborting generation {}", new.generation);
+        sharding::send(
+            redis,
+            &ShardingCommand::Abort {
+                generation: new.generation,
+            },
+        )
+        .await?;
+        sharding::forget(redis, new.generation).await?;
+
+        return Err(err);
+    }
+
+    // store first, so gateways that restart, or missed the commit, from now
+    // on use the new sharding
+    sharding::store(redis, &new).await?;
+    let retired = wait_retired(
+        redis,
+        &new,
+        config.cluster_count,
+        Duration::from_secs(config.ready_timeout),
+    )
+    .await;
+    sharding::forget(redis, new.generation).await?;
+    retired?;
+    forget_removed_shards(redis, shard_count).await?;
+
+    tracing::info!(
+        "resharded to generation {} with {} shards",
+        new.generation,
+        new.shard_count
+    );
+    Ok(())
+}
+
+/// discord's recommendation, rounded up so every identify bucket is used
+fn recommended_shard_count(recommended: u32, max_concurrency: u32) -> u32 {
+    recommended.max(1).next_multiple_of(max_concurrency.max(1))
+}
+
+/// spread `shard_count` shards over `cluster_count` clusters, every cluster
+/// needs at least one shard, gateways can't run a cluster without shards
+fn cluster_size(shard_count: u32, cluster_count: u32) -> Result<u32, String> {
+    let cluster_size = shard_count.div_ceil(cluster_count);
+    if cluster_size.saturating_mul(cluster_count - 1) >= shard_count {
+        return Err(format!(
+            "{} shards can't be spread over {} clusters without leaving some empty, lower CLUSTER_COUNT",
+            shard_count, cluster_count
+        ));
+    }
+
+    Ok(cluster_size)
+}
+
+async fn wait_ready(
+    redis: &RedisConnectionManager,
+    sharding: &Sharding,
+    timeout: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let ready = sharding::ready_count(redis, sharding.generation).await?;
+        tracing::info!("{}/{} shards ready", ready, sharding.shard_count);
+        if ready >= sharding.shard_count {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "only {}/{} shards were ready after {}s",
+                ready,
+                sharding.shard_count,
+                timeout.as_secs()
+            )
+            .into());
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// commit `sharding` and wait until every cluster retired its older shards,
+/// commits are resent because pub/sub doesn't keep them for gateways that
+/// weren't subscribed
+async fn wait_retired(
+    redis: &RedisConnectionManager,
+    sharding: &Sharding,
+    cluster_count: u32,
+    timeout: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        sharding::send(
+            redis,
+            &ShardingCommand::Commit {
+                generation: sharding.generation,
+            },
+        )
+        .await?;
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let retired = sharding::retired_count(redis, sharding.generation).await?;
+        tracing::info!(
+            "{}/{} clusters switched to generation {}",
+            retired,
+            cluster_count,
+            sharding.generation
+        );
+        if retired >= cluster_count {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "only {}/{} clusters switched after {}s, the others switch once they notice the new sharding",
+                retired,
+                cluster_count,
+                timeout.as_secs()
+            )
+            .into());
+        }
+    }
+}
+
+/// remove the stats of shards that don't exist anymore after resharding to
+/// fewer shards
+async fn forget_removed_shards(
+    redis: &RedisConnectionManager,
+    shard_count: u32,
+) -> Result<(), Box<dyn Error>> {
+    let mut redis = redis.clone();
+
+    let removed: Vec<String> = redis
+        .hkeys::<_, Vec<String>>("tulpje:shard_status")
+        .await?
+        .into_iter()
+        .filter(|id| id.parse::<u32>().is_ok_and(|id| id >= shard_count))
+        .collect();
+    if !removed.is_empty() {
+        redis
+            .hdel::<_, _, ()>("tulpje:shard_status", removed)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_size() {
+        assert_eq!(cluster_size(16, 4), Ok(4), "shards should be spread evenly");
+        assert_eq!(
+            cluster_size(10, 4),
+            Ok(3),
+            "the last cluster should get the remaining shards"
+        );
+        assert!(
+            cluster_size(6, 4).is_err(),
+            "clusters shouldn't be left without shards"
+        );
+        assert!(
+            cluster_size(2, 4).is_err(),
+            "there should be at least as many shards as clusters"
+        );
+    }
+}
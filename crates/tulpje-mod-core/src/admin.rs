@@ -0,0 +1,110 @@
+use tulpje_common::runtime_config::{self, RuntimeConfig};
+use tulpje_framework::{CommandError, Error};
+use tulpje_lib::{
+    context::{AutocompleteContext, CommandContext},
+    responses,
+    tasks::{self, TaskCommand},
+};
+
+pub(crate) async fn task_list(ctx: CommandContext) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let mut tasks: Vec<_> = ctx.services.registry.tasks.values().collect();
+    tasks.sort_by(|a, b| (&a.module, &a.name).cmp(&(&b.module, &b.name)));
+
+    let mut text = String::from("### Scheduled Tasks\n");
+    for task in tasks {
+        let next_run = if task.is_paused() {
+            "paused".to_string()
+        } else {
+            task.next_run()
+                .map_or_else(|| "never".into(), |at| format!("<t:{}:R>", at.timestamp()))
+        };
+
+        text.push_str(&format!(
+            "- `{}` ({}) next run: {}\n",
+            task.name, task.module, next_run
+        ));
+    }
+
+    responses::info(&ctx, &text).await
+}
+
+async fn send_task_command(
+    ctx: &CommandContext,
+    command: fn(String) -> TaskCommand,
+) -> Result<TaskCommand, Error> {
+    let name = ctx.get_arg_string("name")?;
+    if !ctx.services.registry.tasks.contains_key(&name) {
+        return Err(CommandError::not_found(format!("Unknown task `{name}`")).into());
+    }
+
+    ctx.defer_ephemeral().await?;
+    let command = command(name);
+    tasks::send(&ctx.services.redis, &command).await?;
+
+    Ok(command)
+}
+
+pub(crate) async fn task_run(ctx: CommandContext) -> Result<(), Error> {
+    let command = send_task_command(&ctx, TaskCommand::Run).await?;
+
+    responses::success(
+        &ctx,
+        &format!(
+            "Running `{}`, check `/info tasks` for the result",
+            command.task()
+        ),
+    )
+    .await
+}
+
+pub(crate) async fn task_pause(ctx: CommandContext) -> Result<(), Error> {
+    let command = send_task_command(&ctx, TaskCommand::Pause).await?;
+
+    responses::success(&ctx, &format!("Paused `{}`", command.task())).await
+}
+
+pub(crate) async fn task_resume(ctx: CommandContext) -> Result<(), Error> {
+    let command = send_task_command(&ctx, TaskCommand::Resume).await?;
+
+    responses::success(&ctx, &format!("Resumed `{}`", command.task())).await
+}
+
+pub(crate) async fn task_autocomplete(ctx: AutocompleteContext) -> Result<(), Error> {
+    let query = ctx.focused.value.to_lowercase();
+
+    let mut names: Vec<_> = ctx
+        .services
+        .registry
+        .tasks
+        .keys()
+        .filter(|name| name.to_lowercase().contains(&query))
+        .cloned()
+        .collect();
+    names.sort();
+
+    ctx.respond_strings(names.into_iter().map(|name| (name.clone(), name)))
+        .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn config_view(ctx: CommandContext) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let config = serde_json::to_string_pretty(&*ctx.services.config.get())?;
+    responses::info(&ctx, &format!("### Runtime Config\n```json\n{config}\n```")).await
+}
+
+pub(crate) async fn config_set(ctx: CommandContext) -> Result<(), Error> {
+    let json = ctx.get_arg_string("config")?;
+    // fields that aren't given keep their current value
+    let config = RuntimeConfig::from_json(&json, &ctx.services.config.get())
+        .map_err(|err| CommandError::user(format!("Invalid config: {err}")))?;
+
+    ctx.defer_ephemeral().await?;
+    runtime_config::store(&ctx.services.redis, &config).await?;
+
+    responses::success(&ctx, "Updated the runtime config").await
+}
@@ -1,7 +1,10 @@
 use tulpje_framework::Error;
 
 use super::{db, set_guild_commands_for_guild};
-use tulpje_lib::context::CommandContext;
+use tulpje_lib::{
+    context::{AutocompleteContext, CommandContext},
+    guild_modules,
+};
 
 pub(crate) async fn enable(ctx: CommandContext) -> Result<(), Error> {
     let Some(guild) = ctx.guild().await? else {
@@ -15,11 +18,12 @@ pub(crate) async fn enable(ctx: CommandContext) -> Result<(), Error> {
     }
 
     db::enable_module(&ctx.services.db, guild.id, &module).await?;
+    guild_modules::invalidate(&ctx.services.registry, &ctx.services.redis, guild.id).await?;
     set_guild_commands_for_guild(
         &db::guild_modules(&ctx.services.db, guild.id).await?,
         guild.id,
         ctx.interaction(),
-        &ctx.services.registry,
+        &ctx.services,
     )
     .await?;
 
@@ -28,6 +32,28 @@ pub(crate) async fn enable(ctx: CommandContext) -> Result<(), Error> {
     Ok(())
 }
 
+/// suggest modules that aren't enabled yet for this server
+pub(crate) async fn enable_autocomplete(ctx: AutocompleteContext) -> Result<(), Error> {
+    let Some(guild_id) = ctx.event.guild_id else {
+        unreachable!("command is guild_only");
+    };
+
+    let enabled = db::guild_modules(&ctx.services.db, guild_id).await?;
+    let query = ctx.focused.value.to_lowercase();
+
+    ctx.respond_strings(
+        ctx.services
+            .registry
+            .guild_module_names()
+            .into_iter()
+            .filter(|m| !enabled.contains(m) && m.to_lowercase().contains(&query))
+            .map(|m| (m.clone(), m)),
+    )
+    .await?;
+
+    Ok(())
+}
+
 pub(crate) async fn disable(ctx: CommandContext) -> Result<(), Error> {
     let Some(guild) = ctx.guild().await? else {
         unreachable!("command is guild_only");
@@ -40,11 +66,12 @@ pub(crate) async fn disable(ctx: CommandContext) -> Result<(), Error> {
     }
 
     db::disable_module(&ctx.services.db, guild.id, &module).await?;
+    guild_modules::invalidate(&ctx.services.registry, &ctx.services.redis, guild.id).await?;
     set_guild_commands_for_guild(
         &db::guild_modules(&ctx.services.db, guild.id).await?,
         guild.id,
         ctx.interaction(),
-        &ctx.services.registry,
+        &ctx.services,
     )
     .await?;
 
@@ -66,7 +66,6 @@ pub(crate) async fn guilds_with_module(
     .collect())
 }
 
-#[expect(dead_code, reason = "useful utility function")]
 pub(crate) async fn all_guild_modules(
     db: &sqlx::PgPool,
 ) -> Result<HashMap<Id<GuildMarker>, Vec<String>>, Error> {
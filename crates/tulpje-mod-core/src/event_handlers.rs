@@ -1,9 +1,12 @@
 use tulpje_framework::Error;
-use tulpje_lib::context::EventContext;
+use tulpje_lib::{
+    commands::{CommandScope, forget_commands},
+    context::EventContext,
+};
 use twilight_gateway::Event;
 use twilight_model::id::{Id, marker::GuildMarker};
 
-use crate::db;
+use crate::{db, set_guild_commands_for_guild};
 
 pub async fn guild_create(ctx: EventContext) -> Result<(), Error> {
     let Event::GuildCreate(guild_create) = &ctx.event else {
@@ -32,6 +35,9 @@ pub async fn guild_delete(ctx: EventContext) -> Result<(), Error> {
 
     tracing::info!("was removed from guild `{}`", guild_delete.id);
     db::leave_guild(&ctx.services.db, guild_delete.id).await?;
+    // discord drops our commands along with the guild, make sure they're
+    // registered again if we ever get re-added
+    forget_commands(&ctx.services.redis, CommandScope::Guild(guild_delete.id)).await?;
 
     Ok(())
 }
@@ -39,17 +45,11 @@ pub async fn guild_delete(ctx: EventContext) -> Result<(), Error> {
 pub async fn register_commands(ctx: &EventContext, guild_id: Id<GuildMarker>) -> Result<(), Error> {
     tracing::debug!("registering commands for guild {}", guild_id);
 
-    let commands: Vec<_> = db::guild_modules(&ctx.services.db, guild_id)
-        .await?
-        .iter()
-        .filter_map(|name| ctx.services.registry.module_commands(name))
-        .flatten()
-        .collect();
-
-    ctx.client
-        .interaction(ctx.application_id)
-        .set_guild_commands(guild_id, &commands)
-        .await?;
-
-    Ok(())
+    set_guild_commands_for_guild(
+        &db::guild_modules(&ctx.services.db, guild_id).await?,
+        guild_id,
+        ctx.client.interaction(ctx.application_id),
+        &ctx.services,
+    )
+    .await
 }
@@ -1,4 +1,4 @@
-use twilight_gateway::EventType;
+use twilight_gateway::{EventType, Intents};
 use twilight_http::client::InteractionClient;
 use twilight_model::{
     application::{
@@ -11,14 +11,19 @@ use twilight_model::{
 use twilight_util::builder::command::StringBuilder;
 
 use tulpje_framework::{
-    Error, Module, ModuleBuilder, Registry, handler_func,
-    module::command_builder::{CommandBuilder, SubCommandBuilder},
+    Context, Error, Module, ModuleBuilder, Registry, handler_func,
+    module::command_builder::{CommandBuilder, SubCommandBuilder, SubCommandGroupBuilder},
+};
+use tulpje_lib::{
+    commands::{CommandScope, sync_commands},
+    context::Services,
 };
-use tulpje_lib::context::Services;
 
+mod admin;
 mod commands;
 mod db;
 mod event_handlers;
+mod settings;
 mod tasks;
 
 pub fn build(registry: &Registry<Services>) -> Module<Services> {
@@ -36,11 +41,8 @@ pub fn build(registry: &Registry<Services>) -> Module<Services> {
                 .contexts([InteractionContextType::Guild])
                 .subcommand(
                     SubCommandBuilder::new("enable", "enable a module for this server")
-                        .option(
-                            StringBuilder::new("module", "The module to enable")
-                                .choices(guild_module_choices.clone())
-                                .required(true),
-                        )
+                        .option(StringBuilder::new("module", "The module to enable").required(true))
+                        .autocomplete("module", handler_func!(commands::enable_autocomplete))
                         .handler(handler_func!(commands::enable)),
                 )
                 .subcommand(
@@ -57,7 +59,77 @@ pub fn build(registry: &Registry<Services>) -> Module<Services> {
                         .handler(handler_func!(commands::modules)),
                 ),
         )
+        .command(
+            // only registered in dev guilds, and hidden from everyone but server admins there
+            CommandBuilder::new("admin", "bot administration", CommandType::ChatInput)
+                .owner_only()
+                .default_member_permissions(Permissions::ADMINISTRATOR)
+                .contexts([InteractionContextType::Guild])
+                .group(
+                    SubCommandGroupBuilder::new("task", "manage scheduled tasks")
+                        .subcommand(
+                            SubCommandBuilder::new("list", "list tasks and their next run")
+                                .handler(handler_func!(admin::task_list)),
+                        )
+                        .subcommand(
+                            task_subcommand("run", "run a task now")
+                                .handler(handler_func!(admin::task_run)),
+                        )
+                        .subcommand(
+                            task_subcommand("pause", "stop running a task on its schedule")
+                                .handler(handler_func!(admin::task_pause)),
+                        )
+                        .subcommand(
+                            task_subcommand("resume", "run a paused task on its schedule again")
+                                .handler(handler_func!(admin::task_resume)),
+                        ),
+                )
+                .group(
+                    SubCommandGroupBuilder::new("config", "manage the runtime config")
+                        .subcommand(
+                            SubCommandBuilder::new("view", "show the current runtime config")
+                                .handler(handler_func!(admin::config_view)),
+                        )
+                        .subcommand(
+                            SubCommandBuilder::new("set", "change fields of the runtime config")
+                                .option(
+                                    StringBuilder::new("config", "The fields to change, as json")
+                                        .required(true),
+                                )
+                                .handler(handler_func!(admin::config_set)),
+                        ),
+                ),
+        )
+        .command(
+            CommandBuilder::new("settings", "server settings", CommandType::ChatInput)
+                .default_member_permissions(Permissions::MANAGE_GUILD)
+                .contexts([InteractionContextType::Guild])
+                .subcommand(
+                    settings_subcommand("view", "show the settings of a module")
+                        .handler(handler_func!(settings::view)),
+                )
+                .subcommand(
+                    settings_subcommand("set", "change a setting")
+                        .option(StringBuilder::new("key", "The setting").required(true))
+                        .option(StringBuilder::new("value", "The new value").required(true))
+                        .autocomplete("key", handler_func!(settings::key_autocomplete))
+                        .handler(handler_func!(settings::set)),
+                )
+                .subcommand(
+                    settings_subcommand(
+                        "reset",
+                        "reset a setting, or all settings of a module, to the default",
+                    )
+                    .option(StringBuilder::new(
+                        "key",
+                        "The setting, leave empty for all",
+                    ))
+                    .autocomplete("key", handler_func!(settings::key_autocomplete))
+                    .handler(handler_func!(settings::reset)),
+                ),
+        )
         // events
+        .intents(Intents::GUILDS)
         .event(
             EventType::GuildCreate,
             handler_func!(event_handlers::guild_create),
@@ -75,17 +147,32 @@ pub fn build(registry: &Registry<Services>) -> Module<Services> {
         .build()
 }
 
+fn task_subcommand(name: &str, description: &str) -> SubCommandBuilder<Services> {
+    SubCommandBuilder::new(name, description)
+        .option(StringBuilder::new("name", "The task").required(true))
+        .autocomplete("name", handler_func!(admin::task_autocomplete))
+}
+
+fn settings_subcommand(name: &str, description: &str) -> SubCommandBuilder<Services> {
+    SubCommandBuilder::new(name, description)
+        .option(StringBuilder::new("module", "The module").required(true))
+        .autocomplete("module", handler_func!(settings::module_autocomplete))
+}
+
 pub(crate) async fn set_guild_commands_for_guild(
     modules: &[String],
     guild_id: Id<GuildMarker>,
     interaction: InteractionClient<'_>,
-    registry: &Registry<Services>,
+    services: &Services,
 ) -> Result<(), Error> {
-    let commands: Vec<Command> = modules
+    let mut commands: Vec<Command> = modules
         .iter()
-        .filter_map(|module| registry.module_commands(module))
+        .filter_map(|module| services.registry.module_commands(module))
         .flatten()
         .collect();
+    if services.is_dev_guild(guild_id) {
+        commands.extend(services.registry.owner_commands());
+    }
 
     tracing::debug!(
         "setting commands [{}] for guild {}",
@@ -97,7 +184,44 @@ pub(crate) async fn set_guild_commands_for_guild(
         guild_id
     );
 
-    interaction.set_guild_commands(guild_id, &commands).await?;
+    sync_commands(
+        interaction,
+        &services.redis,
+        CommandScope::Guild(guild_id),
+        &commands,
+    )
+    .await?;
 
     Ok(())
 }
+
+/// register the commands of every guild with modules enabled, and the owner
+/// commands of dev guilds, guilds otherwise only get them on GUILD_CREATE,
+/// which gateways resuming their sessions don't send, guilds that fail are
+/// skipped
+pub async fn sync_guild_commands(ctx: &Context<Services>) -> Result<(), Error> {
+    let mut guilds = db::all_guild_modules(&ctx.services.db).await?;
+    for guild_id in &ctx.services.dev_guilds {
+        guilds.entry(*guild_id).or_default();
+    }
+
+    tracing::info!("registering commands for {} guilds", guilds.len());
+    for (guild_id, modules) in guilds {
+        if let Err(err) =
+            set_guild_commands_for_guild(&modules, guild_id, ctx.interaction(), &ctx.services).await
+        {
+            tracing::warn!("error registering commands for guild {}: {}", guild_id, err);
+        }
+    }
+
+    Ok(())
+}
+
+/// lookup for the modules enabled in a guild, used by the framework to gate
+/// handlers of guild scoped modules, see [`Registry::guild_module_lookup`]
+pub async fn enabled_modules(
+    ctx: Context<Services>,
+    guild_id: Id<GuildMarker>,
+) -> Result<Vec<String>, Error> {
+    db::guild_modules(&ctx.services.db, guild_id).await
+}
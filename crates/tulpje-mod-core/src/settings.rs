@@ -0,0 +1,144 @@
+use serde_json::{Map, Value};
+use twilight_model::application::interaction::application_command::CommandOptionValue;
+
+use tulpje_framework::{CommandError, Error};
+use tulpje_lib::{
+    context::{AutocompleteContext, CommandContext},
+    responses,
+    settings::{self, Schema, Setting},
+};
+
+fn schema<'a>(ctx: &'a CommandContext, module: &str) -> Result<&'a Schema, Error> {
+    ctx.services
+        .registry
+        .settings_schema(module)
+        .ok_or_else(|| CommandError::not_found(format!("`{module}` doesn't have settings")).into())
+}
+
+fn setting<'a>(schema: &'a Schema, key: &str) -> Result<&'a Setting, Error> {
+    schema.setting(key).ok_or_else(|| {
+        CommandError::not_found(format!(
+            "`{}` doesn't have a `{}` setting",
+            schema.module, key
+        ))
+        .into()
+    })
+}
+
+pub(crate) async fn view(ctx: CommandContext) -> Result<(), Error> {
+    let guild_id = ctx.event.guild_id.ok_or("command is guild_only")?;
+    let schema = schema(&ctx, &ctx.get_arg_string("module")?)?;
+    ctx.defer_ephemeral().await?;
+
+    let stored = settings::load(
+        &ctx.services.db,
+        &ctx.services.redis,
+        schema.module,
+        guild_id,
+    )
+    .await?;
+
+    let mut text = format!("### {} Settings\n", schema.module);
+    for setting in &schema.settings {
+        let (value, default) = match stored.get(setting.key) {
+            Some(value) => (value, ""),
+            None => (
+                schema.defaults.get(setting.key).unwrap_or(&Value::Null),
+                " (default)",
+            ),
+        };
+
+        text.push_str(&format!(
+            "- **{}**: {}{}\n  -# {}\n",
+            setting.key,
+            setting.display(value),
+            default,
+            setting.description
+        ));
+    }
+
+    responses::info(&ctx, &text).await
+}
+
+pub(crate) async fn set(ctx: CommandContext) -> Result<(), Error> {
+    let guild_id = ctx.event.guild_id.ok_or("command is guild_only")?;
+    let schema = schema(&ctx, &ctx.get_arg_string("module")?)?;
+    let setting = setting(schema, &ctx.get_arg_string("key")?)?;
+    let value = setting.parse(&ctx.get_arg_string("value")?)?;
+    ctx.defer_ephemeral().await?;
+
+    settings::set_key(
+        &ctx.services.db,
+        &ctx.services.redis,
+        schema.module,
+        guild_id,
+        setting.key,
+        &value,
+    )
+    .await?;
+
+    responses::success(
+        &ctx,
+        &format!("Set **{}** to {}", setting.key, setting.display(&value)),
+    )
+    .await
+}
+
+pub(crate) async fn reset(ctx: CommandContext) -> Result<(), Error> {
+    let guild_id = ctx.event.guild_id.ok_or("command is guild_only")?;
+    let schema = schema(&ctx, &ctx.get_arg_string("module")?)?;
+    let setting = match ctx.get_arg_string_optional("key")? {
+        Some(key) => Some(setting(schema, &key)?),
+        None => None,
+    };
+    ctx.defer_ephemeral().await?;
+
+    let (db, redis) = (&ctx.services.db, &ctx.services.redis);
+    match setting {
+        Some(setting) => {
+            settings::remove_key(db, redis, schema.module, guild_id, setting.key).await?;
+        }
+        None => settings::store(db, redis, schema.module, guild_id, &Map::new()).await?,
+    }
+
+    let text = match setting {
+        Some(setting) => format!("Reset **{}** to its default", setting.key),
+        None => format!("Reset all {} settings to their defaults", schema.module),
+    };
+    responses::success(&ctx, &text).await
+}
+
+pub(crate) async fn module_autocomplete(ctx: AutocompleteContext) -> Result<(), Error> {
+    let query = ctx.focused.value.to_lowercase();
+
+    ctx.respond_strings(
+        ctx.services
+            .registry
+            .settings_schemas()
+            .filter(|schema| schema.module.contains(&query))
+            .map(|schema| (schema.module.to_string(), schema.module.to_string())),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// suggest the settings of the module picked in the `module` option
+pub(crate) async fn key_autocomplete(ctx: AutocompleteContext) -> Result<(), Error> {
+    let query = ctx.focused.value.to_lowercase();
+    let schema = match ctx.options.get("module") {
+        Some(CommandOptionValue::String(module)) => ctx.services.registry.settings_schema(module),
+        _ => None,
+    };
+
+    ctx.respond_strings(
+        schema
+            .into_iter()
+            .flat_map(|schema| &schema.settings)
+            .filter(|setting| setting.key.contains(&query))
+            .map(|setting| (setting.key.to_string(), setting.key.to_string())),
+    )
+    .await?;
+
+    Ok(())
+}
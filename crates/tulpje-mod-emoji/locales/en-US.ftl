@@ -0,0 +1,15 @@
+# messages for the emoji module, command names and descriptions are defined in
+# code, so only other locales need `cmd-` entries
+
+sort-count_desc = Most Used
+sort-count_asc = Least Used
+sort-date_desc = Most Recent
+sort-date_asc = Least Recent
+
+stats-title = { $sort } Emotes in { $guild }
+stats-entry = { $emoji } • Used { $count } times • Last used <t:{ $timestamp }:R>
+stats-no-data = No Data
+stats-page = Page { $page } of { $total }
+stats-degraded = ⚠️ bot is currently only tracking emoji reactions as it can't access message content. We're working on a fix
+
+maintenance-done = cleaned up { $count } deleted emotes
@@ -0,0 +1,35 @@
+sort-count_desc = Meest Gebruikt
+sort-count_asc = Minst Gebruikt
+sort-date_desc = Meest Recent
+sort-date_asc = Minst Recent
+
+stats-title = { $sort } Emotes in { $guild }
+stats-entry = { $emoji } • { $count } keer gebruikt • Laatst gebruikt <t:{ $timestamp }:R>
+stats-no-data = Geen Gegevens
+stats-page = Pagina { $page } van { $total }
+stats-degraded = ⚠️ de bot houdt momenteel alleen emoji reacties bij omdat hij de inhoud van berichten niet kan lezen. We werken aan een oplossing
+
+maintenance-done = { $count } verwijderde emotes opgeruimd
+
+cmd-emoji =
+    .description = emoji commando's
+cmd-emoji-stats =
+    .name = statistieken
+    .description = statistieken van de emojis in deze server
+cmd-emoji-stats-sort =
+    .name = sorteren
+    .description = Hoe de emojis gesorteerd worden
+cmd-emoji-clone =
+    .name = kopieer
+    .description = kopieer een emoji naar deze server
+cmd-emoji-clone-emoji =
+    .description = emojis om te kopiëren
+cmd-emoji-clone-new_name =
+    .name = nieuwe_naam
+    .description = nieuwe naam (alleen bij het kopiëren van een enkele emoji)
+cmd-emoji-clone-prefix =
+    .name = voorvoegsel
+    .description = voorvoegsel voor de nieuwe emoji(s)
+cmd-emoji-maintenance =
+    .name = onderhoud
+    .description = verwijderde emojis uit de statistieken halen
@@ -19,7 +19,7 @@ use twilight_util::builder::{
     embed::{EmbedBuilder, EmbedFooterBuilder},
 };
 
-use tulpje_framework::Error;
+use tulpje_framework::{Error, i18n::Args};
 use tulpje_lib::context::{CommandContext, ComponentInteractionContext};
 
 use super::db;
@@ -50,60 +50,55 @@ fn create_emoji_stats_sort_menu() -> SelectMenu {
     }
 }
 
-fn create_emoji_stats_pagination_buttons(current_page: u16, total_pages: u16) -> Vec<Component> {
-    vec![
-        Component::Button(Button {
-            id: None,
-            custom_id: Some(String::from("emoji_stats_first_page")),
-            disabled: current_page == 1,
-            style: ButtonStyle::Primary,
-            emoji: Some(EmojiReactionType::Unicode {
-                name: String::from("⏮️"),
-            }),
-
-            label: None,
-            url: None,
-            sku_id: None,
-        }),
-        Component::Button(Button {
-            id: None,
-            custom_id: Some(String::from("emoji_stats_prev_page")),
-            disabled: current_page == 1,
-            style: ButtonStyle::Primary,
-            emoji: Some(EmojiReactionType::Unicode {
-                name: String::from("◀️"),
-            }),
-
-            label: None,
-            url: None,
-            sku_id: None,
-        }),
-        Component::Button(Button {
-            id: None,
-            custom_id: Some(String::from("emoji_stats_next_page")),
-            disabled: current_page == total_pages,
-            style: ButtonStyle::Primary,
-            emoji: Some(EmojiReactionType::Unicode {
-                name: String::from("▶️"),
-            }),
-
-            label: None,
-            url: None,
-            sku_id: None,
-        }),
-        Component::Button(Button {
-            id: None,
-            custom_id: Some(String::from("emoji_stats_last_page")),
-            disabled: current_page == total_pages,
-            style: ButtonStyle::Primary,
-            emoji: Some(EmojiReactionType::Unicode {
-                name: String::from("⏭️"),
-            }),
-
-            label: None,
-            url: None,
-            sku_id: None,
+fn create_emoji_stats_pagination_button(
+    action: &str,
+    emoji: &str,
+    disabled: bool,
+    current_page: u16,
+    sort: &StatsSort,
+) -> Component {
+    Component::Button(Button {
+        id: None,
+        custom_id: Some(format!(
+            "emoji_stats:page:{}:{}:{}",
+            action,
+            current_page,
+            sort.id()
+        )),
+        disabled,
+        style: ButtonStyle::Primary,
+        emoji: Some(EmojiReactionType::Unicode {
+            name: String::from(emoji),
         }),
+
+        label: None,
+        url: None,
+        sku_id: None,
+    })
+}
+
+fn create_emoji_stats_pagination_buttons(
+    current_page: u16,
+    total_pages: u16,
+    sort: &StatsSort,
+) -> Vec<Component> {
+    vec![
+        create_emoji_stats_pagination_button("first", "⏮️", current_page == 1, current_page, sort),
+        create_emoji_stats_pagination_button("prev", "◀️", current_page == 1, current_page, sort),
+        create_emoji_stats_pagination_button(
+            "next",
+            "▶️",
+            current_page == total_pages,
+            current_page,
+            sort,
+        ),
+        create_emoji_stats_pagination_button(
+            "last",
+            "⏭️",
+            current_page == total_pages,
+            current_page,
+            sort,
+        ),
     ]
 }
 
@@ -113,6 +108,8 @@ async fn create_emoji_stats_embed(
     sort: &StatsSort,
     current_page: u16,
     total_pages: u16,
+    message_content: bool,
+    t: impl Fn(&str, &Args<'_>) -> String,
 ) -> Result<Embed, Error> {
     let emoji_stats = db::get_emoji_stats(
         db,
@@ -126,41 +123,52 @@ async fn create_emoji_stats_embed(
         emoji_stats
             .into_iter()
             .map(|emoji_stats| {
-                format!(
-                    "{} • Used {} times • Last used <t:{}:R>",
-                    emoji_stats.emoji,
-                    emoji_stats.times_used,
-                    emoji_stats.last_used_at.and_utc().timestamp(),
+                t(
+                    "stats-entry",
+                    &[
+                        ("emoji", &emoji_stats.emoji),
+                        ("count", &emoji_stats.times_used),
+                        ("timestamp", &emoji_stats.last_used_at.and_utc().timestamp()),
+                    ],
                 )
             })
             .collect::<Vec<String>>()
             .join("\n")
     } else {
-        "No Data".to_string()
+        t("stats-no-data", &[])
     };
 
-    let degraded_warn = if std::env::var("TULPJE_MESSAGE_CONTENT")
-        .unwrap_or_else(|_| "true".to_string())
-        == "true"
-    {
-        ""
+    let degraded_warn = if message_content {
+        String::new()
     } else {
-        "\n\n⚠️ bot is currently only tracking emoji reactions as it can't access message content. We're working on a fix"
+        format!("\n\n{}", t("stats-degraded", &[]))
     };
 
     let mut builder = EmbedBuilder::new()
-        .title(format!("{} Emotes in {}", sort.name(), guild.name))
+        .title(t(
+            "stats-title",
+            &[
+                ("sort", &t(&format!("sort-{}", sort.id()), &[])),
+                ("guild", &guild.name),
+            ],
+        ))
         .description(format!("{emoji_str}{degraded_warn}"));
 
     if total_pages > 0 {
         builder = builder.footer(
-            EmbedFooterBuilder::new(format!("Page {} of {}", current_page, total_pages)).build(),
+            EmbedFooterBuilder::new(t(
+                "stats-page",
+                &[("page", &current_page), ("total", &total_pages)],
+            ))
+            .build(),
         );
     }
 
     Ok(builder.validate()?.build())
 }
 
+/// extract the page and sort from stats messages posted before the buttons
+/// carried them in their custom_id, those were always in english
 fn extract_page_and_sort(event: &InteractionCreate) -> Option<(u16, StatsSort)> {
     let Some(ref message) = event.message else {
         tracing::trace!("extract_page: message is None");
@@ -216,35 +224,64 @@ fn extract_page_and_sort(event: &InteractionCreate) -> Option<(u16, StatsSort)>
 }
 
 pub async fn handle_emoji_pagination(ctx: ComponentInteractionContext) -> Result<(), Error> {
-    let guild = ctx.guild().await?.ok_or("not in guild")?;
+    let page: u16 = ctx.parse_param("page")?;
+    let sort = StatsSort::try_from_string(ctx.param("sort")?)?;
+    let action = ctx.param("action")?.to_string();
 
-    ctx.response(InteractionResponse {
-        kind: InteractionResponseType::DeferredUpdateMessage,
-        data: None,
-    })
-    .await?;
+    change_page(&ctx, &action, page, sort).await
+}
 
+/// pagination buttons of stats messages posted before they carried their
+/// state, e.g. `emoji_stats_next_page`
+pub async fn handle_legacy_emoji_pagination(ctx: ComponentInteractionContext) -> Result<(), Error> {
     let Some((page, sort)) = extract_page_and_sort(&ctx.event) else {
-        tracing::warn!("handle_emoji_pagination: couldn't parse page id from event");
+        tracing::warn!("handle_legacy_emoji_pagination: couldn't parse page id from event");
         return Ok(());
     };
 
-    let total_pages = get_total_pages(&ctx.services.db, guild.id).await?;
-
-    let new_page = match ctx.interaction.custom_id.as_str() {
-        "emoji_stats_first_page" => 1,
-        "emoji_stats_prev_page" => max(page - 1, 1),
-        "emoji_stats_next_page" => min(page + 1, total_pages),
-        "emoji_stats_last_page" => total_pages,
+    let action = match ctx.interaction.custom_id.as_str() {
+        "emoji_stats_first_page" => "first",
+        "emoji_stats_prev_page" => "prev",
+        "emoji_stats_next_page" => "next",
+        "emoji_stats_last_page" => "last",
         other => {
             return Err(format!(
-                "unknown interaction id for handle_emoji_pagination: {}",
+                "unknown interaction id for handle_legacy_emoji_pagination: {}",
                 other
             )
             .into());
         }
     };
 
+    change_page(&ctx, action, page, sort).await
+}
+
+async fn change_page(
+    ctx: &ComponentInteractionContext,
+    action: &str,
+    page: u16,
+    sort: StatsSort,
+) -> Result<(), Error> {
+    let guild = ctx.guild().await?.ok_or("not in guild")?;
+
+    ctx.response(InteractionResponse {
+        kind: InteractionResponseType::DeferredUpdateMessage,
+        data: None,
+    })
+    .await?;
+
+    let total_pages = get_total_pages(&ctx.services.db, guild.id).await?;
+
+    let new_page = match action {
+        "first" => 1,
+        "prev" => max(page.saturating_sub(1), 1),
+        "next" => min(page + 1, total_pages),
+        "last" => total_pages,
+        other => {
+            return Err(format!("unknown pagination action for emoji stats: {}", other).into());
+        }
+    };
+
     if let Err(err) = ctx
         .interaction()
         .update_response(&ctx.event.token)
@@ -254,9 +291,11 @@ pub async fn handle_emoji_pagination(ctx: ComponentInteractionContext) -> Result
             &sort,
             new_page,
             total_pages,
+            ctx.services.config.get().message_content,
+            |key, args| ctx.t(key, args),
         )
         .await?]))
-        .components(Some(&get_components(new_page, total_pages)))
+        .components(Some(&get_components(new_page, total_pages, &sort)))
         .await
     {
         tracing::warn!(?err, "failed to update message");
@@ -264,6 +303,7 @@ pub async fn handle_emoji_pagination(ctx: ComponentInteractionContext) -> Result
 
     Ok(())
 }
+
 pub async fn handle_emoji_stats_sort(ctx: ComponentInteractionContext) -> Result<(), Error> {
     if ctx.interaction.custom_id != "emoji_stats_sort" {
         tracing::debug!(
@@ -300,9 +340,11 @@ pub async fn handle_emoji_stats_sort(ctx: ComponentInteractionContext) -> Result
             &sort,
             1, // we reset back to first page after resetting sorting method
             total_pages,
+            ctx.services.config.get().message_content,
+            |key, args| ctx.t(key, args),
         )
         .await?]))
-        .components(Some(&get_components(1, total_pages)))
+        .components(Some(&get_components(1, total_pages, &sort)))
         .await
     {
         tracing::warn!(?err, "failed to update message");
@@ -331,11 +373,17 @@ pub async fn cmd_emoji_stats(ctx: CommandContext) -> Result<(), Error> {
         kind: InteractionResponseType::ChannelMessageWithSource,
         data: Some(
             InteractionResponseDataBuilder::new()
-                .embeds([
-                    create_emoji_stats_embed(&ctx.services.db, &guild, &sort, 1, total_pages)
-                        .await?,
-                ])
-                .components(get_components(1, total_pages))
+                .embeds([create_emoji_stats_embed(
+                    &ctx.services.db,
+                    &guild,
+                    &sort,
+                    1,
+                    total_pages,
+                    ctx.services.config.get().message_content,
+                    |key, args| ctx.t(key, args),
+                )
+                .await?])
+                .components(get_components(1, total_pages, &sort))
                 .build(),
         ),
     };
@@ -362,13 +410,13 @@ pub async fn cmd_emoji_maintenance(ctx: CommandContext) -> Result<(), Error> {
     let count =
         db::delete_emojis_not_in_list_for_guild(&ctx.services.db, guild.id, emoji_ids).await?;
 
-    ctx.update(format!("cleaned up {} deleted emotes", count))
+    ctx.update(ctx.t("maintenance-done", &[("count", &count)]))
         .await?;
 
     Ok(())
 }
 
-fn get_components(current_page: u16, total_pages: u16) -> Vec<Component> {
+fn get_components(current_page: u16, total_pages: u16, sort: &StatsSort) -> Vec<Component> {
     if total_pages == 0 {
         return vec![];
     }
@@ -381,7 +429,7 @@ fn get_components(current_page: u16, total_pages: u16) -> Vec<Component> {
         .into(),
         ActionRow {
             id: None,
-            components: create_emoji_stats_pagination_buttons(current_page, total_pages),
+            components: create_emoji_stats_pagination_buttons(current_page, total_pages, sort),
         }
         .into(),
     ]
@@ -14,6 +14,7 @@ use tulpje_framework::Error;
 use tulpje_lib::{context::EventContext, util::is_pk_proxy};
 
 use super::{
+    EmojiSettings,
     db::{self, delete_emojis_not_in_list_for_guild},
     shared,
 };
@@ -143,6 +144,16 @@ pub async fn reaction_add(ctx: EventContext) -> Result<(), Error> {
                 return Ok(());
             };
 
+            if !ctx
+                .services
+                .settings::<EmojiSettings>()
+                .get(guild_id)
+                .await?
+                .track_reactions
+            {
+                return Ok(());
+            }
+
             if !shared::is_guild_emoji(&ctx.client, &ctx.services.cache, guild_id, *id).await? {
                 return Ok(());
             }
@@ -2,9 +2,14 @@ mod clone;
 mod commands;
 mod db;
 mod event_handlers;
+mod settings;
 mod shared;
 
-use twilight_gateway::EventType;
+pub use settings::EmojiSettings;
+
+use std::time::Duration;
+
+use twilight_gateway::{EventType, Intents};
 use twilight_model::{
     application::{command::CommandType, interaction::InteractionContextType},
     guild::Permissions,
@@ -12,7 +17,10 @@ use twilight_model::{
 use twilight_util::builder::command::StringBuilder;
 
 use tulpje_framework::{
-    Module, ModuleBuilder, handler_func,
+    Module, ModuleBuilder,
+    cooldown::Cooldown,
+    handler_func,
+    i18n::DEFAULT_LOCALE,
     module::command_builder::{CommandBuilder, SubCommandBuilder},
 };
 
@@ -20,6 +28,10 @@ use tulpje_lib::context::Services;
 
 pub fn build() -> Module<Services> {
     ModuleBuilder::<Services>::new("emoji")
+        // messages
+        .locale(DEFAULT_LOCALE, include_str!("../locales/en-US.ftl"))
+        .locale("nl", include_str!("../locales/nl.ftl"))
+        .settings::<EmojiSettings>()
         // commands
         .command(
             CommandBuilder::new("emoji", "emoji related commands", CommandType::ChatInput)
@@ -45,6 +57,8 @@ pub fn build() -> Module<Services> {
                             "new name (only if cloning a single emoji)",
                         ))
                         .option(StringBuilder::new("prefix", "prefix for new emoji(s)"))
+                        .cooldown(Cooldown::per_user(5, Duration::from_secs(60)))
+                        .bot_permissions(Permissions::CREATE_GUILD_EXPRESSIONS)
                         .handler(handler_func!(clone::command)),
                 )
                 .subcommand(
@@ -56,6 +70,7 @@ pub fn build() -> Module<Services> {
             CommandBuilder::new("Clone Emojis", "", CommandType::Message)
                 .default_member_permissions(Permissions::MANAGE_GUILD_EXPRESSIONS)
                 .contexts([InteractionContextType::Guild])
+                .bot_permissions(Permissions::CREATE_GUILD_EXPRESSIONS)
                 .handler(handler_func!(clone::context_command)),
         )
         // component interactions
@@ -65,22 +80,35 @@ pub fn build() -> Module<Services> {
         )
         //// pagination
         .component(
-            "emoji_stats_first_page",
+            "emoji_stats:page:{action}:{page}:{sort}",
             handler_func!(commands::handle_emoji_pagination),
         )
+        // buttons of stats messages posted before pagination used the above
+        .component(
+            "emoji_stats_first_page",
+            handler_func!(commands::handle_legacy_emoji_pagination),
+        )
         .component(
             "emoji_stats_prev_page",
-            handler_func!(commands::handle_emoji_pagination),
+            handler_func!(commands::handle_legacy_emoji_pagination),
         )
         .component(
             "emoji_stats_next_page",
-            handler_func!(commands::handle_emoji_pagination),
+            handler_func!(commands::handle_legacy_emoji_pagination),
         )
         .component(
             "emoji_stats_last_page",
-            handler_func!(commands::handle_emoji_pagination),
+            handler_func!(commands::handle_legacy_emoji_pagination),
+        )
+        // event handlers, emojis are counted from message content, without it
+        // only reactions are counted
+        .intents(
+            Intents::GUILDS
+                | Intents::GUILD_EMOJIS_AND_STICKERS
+                | Intents::GUILD_MESSAGES
+                | Intents::GUILD_MESSAGE_REACTIONS
+                | Intents::MESSAGE_CONTENT,
         )
-        // event handlers
         .event(
             EventType::MessageCreate,
             handler_func!(event_handlers::handle_message),
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use tulpje_lib::settings::{Setting, SettingKind, Settings};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmojiSettings {
+    /// count reactions as emoji uses, not just messages
+    pub track_reactions: bool,
+}
+
+impl Default for EmojiSettings {
+    fn default() -> Self {
+        Self {
+            track_reactions: true,
+        }
+    }
+}
+
+impl Settings for EmojiSettings {
+    const MODULE: &'static str = "emoji";
+
+    fn schema() -> Vec<Setting> {
+        vec![Setting::new(
+            "track_reactions",
+            "count reactions as emoji uses",
+            SettingKind::Bool,
+        )]
+    }
+}
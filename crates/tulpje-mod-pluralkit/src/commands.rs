@@ -1,6 +1,6 @@
 use pkrs_fork::model::PkId;
 use reqwest::StatusCode;
-use tulpje_framework::Error;
+use tulpje_framework::{CommandError, Error};
 
 use super::{
     db::{self, ModPkSystem},
@@ -33,13 +33,9 @@ pub async fn setup_pk(ctx: CommandContext) -> Result<(), Error> {
                 .status()
                 .is_some_and(|status| status == StatusCode::NOT_FOUND) =>
         {
-            responses::error(
-                &ctx,
-                &format!("### Error\nCouldn't find system `{system_ref}`"),
-            )
-            .await?;
-
-            return Ok(());
+            return Err(
+                CommandError::not_found(format!("Couldn't find system `{system_ref}`")).into(),
+            );
         }
         Err(err) => return Err(err.into()),
     };
@@ -1,6 +1,10 @@
+use std::time::Duration;
+
+use twilight_model::guild::Permissions;
 use twilight_util::builder::command::StringBuilder;
 
 use tulpje_framework::{
+    cooldown::Cooldown,
     handler_func,
     module::command_builder::{SubCommandBuilder, SubCommandGroupBuilder},
 };
@@ -22,10 +26,14 @@ pub(crate) fn commands() -> SubCommandGroupBuilder<Services> {
                         .max_length(100)
                         .required(true),
                 )
+                .bot_permissions(Permissions::MANAGE_CHANNELS)
                 .handler(handler_func!(setup::handle)),
         )
         .subcommand(
             SubCommandBuilder::new("update", "manually update fronter channels")
+                // fronter channel renames are heavily rate limited by discord
+                .cooldown(Cooldown::per_guild(1, Duration::from_secs(30)))
+                .bot_permissions(Permissions::MANAGE_CHANNELS)
                 .handler(handler_func!(update::handle)),
         )
 }
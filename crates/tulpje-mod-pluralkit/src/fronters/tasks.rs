@@ -16,14 +16,17 @@ use twilight_model::{
     util::Timestamp,
 };
 
-use tulpje_framework::Error;
+use tulpje_framework::{Error, job::Jobs};
 use twilight_util::builder::embed::EmbedBuilder;
 use uuid::Uuid;
 
 use crate::{
     db::{self as pk_db, ModPkGuildRow, ModPkSystem},
     fronters::db,
-    notify::db::{self as notify_db, get_notify_channel},
+    notify::{
+        db::{self as notify_db, get_notify_channel},
+        retry::{self, FailedNotification},
+    },
     util::get_member_name,
 };
 
@@ -195,6 +198,7 @@ fn create_front_change_embed(system: &ModPkSystem, switch: &Switch) -> Result<Em
 async fn notify_front_change(
     db: &sqlx::PgPool,
     discord_client: &Arc<Client>,
+    jobs: &Jobs,
     system: &ModPkSystem,
     switch: &Switch,
 ) -> Result<(), Error> {
@@ -239,6 +243,21 @@ async fn notify_front_change(
                 channel_id,
                 err
             );
+
+            if retry::should_retry(&err) {
+                let notification = FailedNotification {
+                    channel_id: *channel_id,
+                    embed: embed.clone(),
+                };
+                if let Err(err) = retry::schedule(jobs, &notification).await {
+                    tracing::warn!(
+                        method = "notify_front_change",
+                        "error scheduling retry of front change notification to guild {}: {}",
+                        guild_id,
+                        err
+                    );
+                }
+            }
         } else {
             metrics::counter!("pk:notifications", "type" => "success").increment(1);
         }
@@ -251,6 +270,7 @@ async fn process_system(
     pk_client: &PkClient,
     discord_client: &Arc<Client>,
     cache: &Cache,
+    jobs: &Jobs,
     system: &ModPkSystem,
 ) -> Result<(), Error> {
     let changed = update_system_fronters(db, system, pk_client).await?;
@@ -258,7 +278,7 @@ async fn process_system(
         FrontChange::Changed(switch) => {
             tracing::debug!("front changed for {}", system.uuid);
             update_fronter_category(db, pk_client, discord_client, cache, system, &switch).await?;
-            notify_front_change(db, discord_client, system, &switch).await?;
+            notify_front_change(db, discord_client, jobs, system, &switch).await?;
         }
         FrontChange::Unchanged => {
             tracing::debug!("front unchanged for {}", system.uuid);
@@ -289,6 +309,7 @@ pub(crate) async fn update_fronters(ctx: TaskContext) -> Result<(), Error> {
             &ctx.services.pk,
             &ctx.client,
             &ctx.services.cache,
+            &ctx.services.jobs,
             system,
         )
         .await
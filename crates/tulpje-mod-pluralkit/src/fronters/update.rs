@@ -1,4 +1,4 @@
-use tulpje_framework::Error;
+use tulpje_framework::{CommandError, Error};
 use tulpje_lib::{context::CommandContext, responses};
 
 use super::{db, shared::update_fronter_channels};
@@ -12,17 +12,16 @@ pub(crate) async fn handle(ctx: CommandContext) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
     let Some(gs) = get_guild_settings_for_id(&ctx.services.db, guild.id).await? else {
-        responses::error(&ctx, "PluralKit module not set-up, please run `/pk setup`").await?;
-        return Ok(());
+        return Err(
+            CommandError::user("PluralKit module not set-up, please run `/pk setup`").into(),
+        );
     };
 
     let Some(cat_id) = db::get_fronter_category(&ctx.services.db, guild.id).await? else {
-        responses::error(
-            &ctx,
+        return Err(CommandError::user(
             "Fronter category not set-up, please run `/pk fronters setup`",
         )
-        .await?;
-        return Ok(());
+        .into());
     };
 
     let cat = ctx.client().channel(*cat_id).await?.model().await?;
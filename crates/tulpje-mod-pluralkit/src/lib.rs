@@ -5,7 +5,7 @@ use twilight_model::{
 use twilight_util::builder::command::StringBuilder;
 
 use tulpje_framework::{
-    Module, ModuleBuilder, handler_func,
+    Module, ModuleBuilder, handler_func, job_func,
     module::command_builder::{CommandBuilder, SubCommandBuilder},
 };
 
@@ -54,5 +54,7 @@ pub fn build() -> Module<Services> {
             "@daily", // once a day at midnight
             handler_func!(tasks::cleanup_systems),
         )
+        // jobs
+        .job(notify::retry::RETRY_JOB, job_func!(notify::retry::handle))
         .build()
 }
@@ -10,6 +10,7 @@ mod add;
 pub(super) mod db;
 mod list;
 mod remove;
+pub(crate) mod retry;
 mod setup;
 mod shared;
 
@@ -50,6 +51,7 @@ pub(crate) fn commands() -> SubCommandGroupBuilder<Services> {
                     .max_length(36)
                     .required(true),
                 )
+                .autocomplete("id", handler_func!(remove::autocomplete))
                 .handler(handler_func!(remove::handle)),
         )
 }
@@ -3,10 +3,13 @@ use tulpje_framework::Error;
 use crate::{
     db::{self as pk_db},
     notify::{db, shared::resolve_system_from_reference},
-    util::handle_system_ref,
+    util::{handle_system_ref, normalize_short_id},
 };
 
-use tulpje_lib::{context::CommandContext, responses};
+use tulpje_lib::{
+    context::{AutocompleteContext, CommandContext},
+    responses,
+};
 
 pub(crate) async fn handle(ctx: CommandContext) -> Result<(), Error> {
     let Some(guild) = ctx.guild().await? else {
@@ -70,3 +73,38 @@ pub(crate) async fn handle(ctx: CommandContext) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// suggest systems the guild currently follows
+pub(crate) async fn autocomplete(ctx: AutocompleteContext) -> Result<(), Error> {
+    let Some(guild_id) = ctx.event.guild_id else {
+        unreachable!("command is guild_only");
+    };
+
+    let system_uuids = db::get_notify_systems(&ctx.services.db, guild_id).await?;
+    let systems = pk_db::get_systems(&ctx.services.db, system_uuids).await?;
+
+    let query = ctx.focused.value.trim().to_lowercase();
+    let short_id_query = normalize_short_id(&query);
+
+    ctx.respond_strings(
+        systems
+            .into_iter()
+            .filter(|system| {
+                system.id.contains(&short_id_query)
+                    || system
+                        .name
+                        .as_ref()
+                        .is_some_and(|name| name.to_lowercase().contains(&query))
+            })
+            .map(|system| {
+                let label = system.name.map_or_else(
+                    || system.id.clone(),
+                    |name| format!("{} ({})", name, system.id),
+                );
+                (label, system.id)
+            }),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::{slice, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use twilight_http::{error::ErrorType, response::StatusCode};
+use twilight_model::{
+    channel::message::Embed,
+    id::{Id, marker::ChannelMarker},
+};
+
+use tulpje_framework::{Error, job::Jobs};
+use tulpje_lib::context::JobContext;
+
+pub(crate) const RETRY_JOB: &str = "pk:retry-notification";
+/// failed notifications are sent again after this, the job runner backs off
+/// further when that fails too
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// a front change notification that couldn't be sent
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct FailedNotification {
+    pub(crate) channel_id: Id<ChannelMarker>,
+    pub(crate) embed: Embed,
+}
+
+/// whether sending might work later, channels that are gone or that the bot
+/// can't access won't come back by themselves
+pub(crate) fn should_retry(err: &twilight_http::Error) -> bool {
+    !matches!(
+        err.kind(),
+        ErrorType::Response { status, .. }
+            if *status == StatusCode::NOT_FOUND || *status == StatusCode::FORBIDDEN
+    )
+}
+
+pub(crate) async fn schedule(jobs: &Jobs, notification: &FailedNotification) -> Result<(), Error> {
+    jobs.schedule_in(RETRY_JOB, RETRY_DELAY, notification)
+        .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle(ctx: JobContext, notification: FailedNotification) -> Result<(), Error> {
+    ctx.client
+        .create_message(notification.channel_id)
+        .embeds(slice::from_ref(&notification.embed))
+        .await?;
+    metrics::counter!("pk:notifications", "type" => "retried").increment(1);
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use tulpje_framework::{
     handler_func,
     module::command_builder::{SubCommandBuilder, SubCommandGroupBuilder},
 };
+use twilight_model::guild::Permissions;
 use twilight_util::builder::command::StringBuilder;
 
 use tulpje_lib::context::Services;
@@ -15,6 +16,7 @@ pub(crate) fn commands() -> SubCommandGroupBuilder<Services> {
             "updates member roles to match the configured system",
         )
         .option(StringBuilder::new("token", "(optional) PluralKit token"))
+        .bot_permissions(Permissions::MANAGE_ROLES)
         .handler(handler_func!(update::handle)),
     )
 }
@@ -76,9 +76,13 @@ pub async fn stats(ctx: CommandContext) -> Result<(), Error> {
         &format!("handler-{}", ctx.services.handler_id),
     )
     .await?;
-    let gateway_stats =
-        redis::get_process_stats(&ctx.services.redis, &format!("gateway-{}", ctx.meta.shard))
-            .await?;
+    // gateway processes can run several shards, older ones ran one each
+    let gateway_process = if current_shard_state.process.is_empty() {
+        format!("gateway-{}", ctx.meta.shard)
+    } else {
+        current_shard_state.process.clone()
+    };
+    let gateway_stats = redis::get_process_stats(&ctx.services.redis, &gateway_process).await?;
 
     #[expect(
         clippy::cast_precision_loss,
@@ -295,3 +299,61 @@ pub async fn processes(ctx: CommandContext) -> Result<(), Error> {
 
     Ok(())
 }
+
+pub async fn tasks(ctx: CommandContext) -> Result<(), Error> {
+    let task_stats = redis::get_all_task_stats(ctx.services.redis.clone()).await?;
+
+    let mut embed = EmbedBuilder::new().title("Tulpje Discord Bot").build();
+
+    if !task_stats.is_empty() {
+        for task in task_stats {
+            let last_run = if task.running > 0 {
+                "running".to_string()
+            } else {
+                task.last_start
+                    .map_or_else(|| "never".into(), |ts| format!("<t:{}:R>", ts))
+            };
+
+            let mut text = format!(
+                "Last Run: {} / Duration: {} / Runs: {} / Failures: {} / Skipped: {}",
+                last_run,
+                task.last_duration_ms.map_or_else(
+                    || "N/A".into(),
+                    |ms| format!("{} ms", ms.to_formatted_string(&Locale::en))
+                ),
+                task.runs.to_formatted_string(&Locale::en),
+                task.failures.to_formatted_string(&Locale::en),
+                task.skipped.to_formatted_string(&Locale::en),
+            );
+            if let Some(last_error) = task.last_error {
+                // embed field values are limited to 1024 characters
+                text.push_str(&format!(
+                    "\nLast Error: `{}`",
+                    last_error.chars().take(500).collect::<String>()
+                ));
+            }
+
+            embed.fields.push(
+                EmbedFieldBuilder::new(format!("{} ({})", task.name, task.module), text).into(),
+            );
+        }
+    } else {
+        embed.description = Some(String::from("No data available"));
+    }
+
+    let response = InteractionResponseDataBuilder::new()
+        .embeds([embed])
+        .build();
+
+    if let Err(err) = ctx
+        .response(InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(response),
+        })
+        .await
+    {
+        tracing::warn!(?err, "failed to respond to command");
+    }
+
+    Ok(())
+}
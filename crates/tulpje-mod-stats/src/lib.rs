@@ -17,8 +17,8 @@ pub fn build() -> Module<Services> {
                 .handler(handler_func!(commands::stats)),
         )
         .command(
-            // TODO: Lock this to a specific guild or list of guilds
             CommandBuilder::new("info", "various bot statistics", CommandType::ChatInput)
+                .owner_only()
                 .contexts([InteractionContextType::Guild])
                 .subcommand(
                     SubCommandBuilder::new("shards", "bot shard stats")
@@ -27,6 +27,10 @@ pub fn build() -> Module<Services> {
                 .subcommand(
                     SubCommandBuilder::new("processes", "bot process stats")
                         .handler(handler_func!(commands::processes)),
+                )
+                .subcommand(
+                    SubCommandBuilder::new("tasks", "scheduled task stats")
+                        .handler(handler_func!(commands::tasks)),
                 ),
         )
         .build()
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use redis::{AsyncCommands as _, aio::ConnectionManager as RedisConnectionManager};
 
 use tulpje_common::{metrics::Metrics, shard_state::ShardState};
-use tulpje_framework::Error;
+use tulpje_framework::{Error, handler::task_handler::TaskStats};
 
 pub async fn get_all_shard_stats(
     redis: RedisConnectionManager,
@@ -38,3 +38,16 @@ pub async fn get_all_process_stats(
         .map(|metrics| (metrics.name.clone(), metrics))
         .collect())
 }
+
+pub async fn get_all_task_stats(redis: RedisConnectionManager) -> Result<Vec<TaskStats>, Error> {
+    let mut stats = redis
+        .clone()
+        .hgetall::<&str, HashMap<String, String>>("tulpje:task_stats")
+        .await?
+        .into_values()
+        .map(|json| serde_json::from_str::<TaskStats>(&json))
+        .collect::<Result<Vec<_>, _>>()?;
+    stats.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(stats)
+}
@@ -90,6 +90,7 @@
             # project binaries
             tulpje-handler = buildCrate "tulpje-handler";
             tulpje-gateway = buildCrate "tulpje-gateway";
+            tulpje-manager = buildCrate "tulpje-manager";
             tulpje-utils = buildCrate "tulpje-utils";
 
             # third party binaries
@@ -104,6 +105,7 @@
               paths = [
                 self'.packages.tulpje-handler
                 self'.packages.tulpje-gateway
+                self'.packages.tulpje-manager
                 self'.packages.tulpje-utils
                 self'.packages.twilight-gateway-queue
                 self'.packages.nirn-proxy
@@ -117,6 +119,9 @@
             docker-handler = pkgs.callPackage ./nix/oci-image.nix {
               main = self'.packages.tulpje-handler;
             };
+            docker-manager = pkgs.callPackage ./nix/oci-image.nix {
+              main = self'.packages.tulpje-manager;
+            };
             docker-gateway-queue = pkgs.callPackage ./nix/oci-image.nix {
               main = self'.packages.twilight-gateway-queue;
               utils = self'.packages.tulpje-utils;
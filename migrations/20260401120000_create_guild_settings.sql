@@ -0,0 +1,9 @@
+CREATE TABLE guild_settings (
+    guild_id BIGINT NOT NULL REFERENCES guilds(guild_id) ON DELETE CASCADE,
+    module VARCHAR(64) NOT NULL,
+    settings JSONB NOT NULL DEFAULT '{}',
+
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (guild_id, module)
+);